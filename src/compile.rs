@@ -38,12 +38,25 @@ pub enum Keyword {
     For,
     /// Divides an identifier from a set of keys in a loop.
     In,
+    /// Applies a test to a value in a condition.
+    Is,
+    /// Beginning of a macro definition.
+    Macro,
+    /// Beginning of an import, or divides the template name from the imported
+    /// macro names.
+    Import,
     /// Beginning of an include block.
     Include,
     /// Beginning of an extends expression.
     Extends,
     /// Beginning of a "block" block.
     Block,
+    /// Beginning of a "raw" block, which is not parsed.
+    Raw,
+    /// Stops the enclosing loop.
+    Break,
+    /// Skips to the next iteration of the enclosing loop.
+    Continue,
     /// End of a block.
     End,
 }
@@ -57,9 +70,15 @@ impl Display for Keyword {
             Keyword::Let => write!(f, "let"),
             Keyword::For => write!(f, "for"),
             Keyword::In => write!(f, "in"),
+            Keyword::Is => write!(f, "is"),
+            Keyword::Macro => write!(f, "macro"),
+            Keyword::Import => write!(f, "import"),
             Keyword::Include => write!(f, "include"),
             Keyword::Extends => write!(f, "extends'"),
             Keyword::Block => write!(f, "block"),
+            Keyword::Raw => write!(f, "raw"),
+            Keyword::Break => write!(f, "break"),
+            Keyword::Continue => write!(f, "continue"),
             Keyword::End => write!(f, "end"),
         }
     }
@@ -88,6 +107,25 @@ pub enum Operator {
     GreaterOrEqual,
     /// <=
     LesserOrEqual,
+    /// in
+    In,
+    /// not in
+    NotIn,
+}
+
+impl Operator {
+    /// Return the binding power of an arithmetic [`Operator`], or [`None`] if
+    /// the `Operator` is used for comparison.
+    ///
+    /// An `Operator` with higher precedence is applied first, so `*` and `/`
+    /// are applied before `+` and `-`.
+    pub fn get_precedence(&self) -> Option<u8> {
+        match self {
+            Operator::Add | Operator::Subtract => Some(1),
+            Operator::Multiply | Operator::Divide => Some(2),
+            _ => None,
+        }
+    }
 }
 
 impl Display for Operator {
@@ -103,6 +141,8 @@ impl Display for Operator {
             Operator::NotEqual => write!(f, "!="),
             Operator::GreaterOrEqual => write!(f, ">="),
             Operator::LesserOrEqual => write!(f, "<="),
+            Operator::In => write!(f, "in"),
+            Operator::NotIn => write!(f, "not in"),
         }
     }
 }
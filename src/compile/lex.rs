@@ -11,6 +11,10 @@ use super::{
 
 use morel::Finder;
 
+/// Keywords that open a block closed by "end", which must be balanced inside
+/// of a "raw" block.
+const OPENING_KEYWORDS: [&str; 5] = ["if", "for", "block", "macro", "raw"];
+
 /// Provides methods to read a source string as [`Token`] instances.
 pub struct Lexer<'source> {
     /// Reference to the source text.
@@ -29,6 +33,18 @@ pub struct Lexer<'source> {
     /// Temporary storage for the a [`Token`] that will be read
     /// on the following call to `.next`
     buffer: Option<(Token, Region)>,
+    /// The number of [`Token::LeftParen`], [`Token::LeftBracket`] and
+    /// [`Token::LeftBrace`] read within the current tag that have not been closed.
+    ///
+    /// While greater than zero, `)`, `]` and `}` are read as closing tokens even
+    /// when they begin a marker, so `(a + (b * c))` is valid with the default
+    /// delimiters.
+    depth: usize,
+    /// The last [`Token`] returned by `.next`, other than whitespace.
+    ///
+    /// Some keywords are only recognized after certain tokens, so they remain
+    /// usable as identifiers everywhere else.
+    previous: Option<Token>,
 }
 
 impl<'source> Lexer<'source> {
@@ -42,12 +58,14 @@ impl<'source> Lexer<'source> {
             left_trim: false,
             cursor: 0,
             buffer: None,
+            depth: 0,
+            previous: None,
         }
     }
 
     /// Return the next [`Token`] and [`Region`].
     ///
-    /// Any instance of [`Token::Whitespace`] is ignored.
+    /// Any instance of [`Token::Whitespace`] or [`Token::Comment`] is ignored.
     ///
     /// # Errors
     ///
@@ -56,6 +74,7 @@ impl<'source> Lexer<'source> {
         loop {
             // Always prefer taking from the buffer when possible.
             if let Some(next) = self.buffer.take() {
+                self.previous = Some(next.0);
                 return Ok(Some(next));
             }
             if self.source[self.cursor..].is_empty() {
@@ -70,14 +89,86 @@ impl<'source> Lexer<'source> {
 
             return match result {
                 Some((token, region)) => match token {
-                    Token::Whitespace => continue,
-                    _ => Ok(Some((token, region))),
+                    Token::Whitespace | Token::Comment => continue,
+                    _ => {
+                        self.previous = Some(token);
+                        Ok(Some((token, region)))
+                    }
                 },
                 None => Ok(None),
             };
         }
     }
 
+    /// Return the [`Region`] of the text between the current position and the
+    /// following "end" block, and move the cursor past the "end" block.
+    ///
+    /// The text is not tokenized, so it may contain markers. Blocks opened inside
+    /// of the text are counted, so each of them must be closed by its own "end"
+    /// block before the "end" block of the "raw" block is found. Whitespace is
+    /// trimmed from the `Region` when the surrounding markers request it.
+    ///
+    /// Assumes the cursor is just beyond the "raw" block, which is located at
+    /// the given `Region`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when no "end" block is found.
+    pub fn lex_raw(&mut self, raw: Region) -> Result<Region, Error> {
+        let mut from = self.cursor;
+        let mut nested = 0;
+        while let Some((id, marker_begin, marker_end)) = self.finder.next(self.source, from) {
+            from = marker_begin + next_len(&self.source[marker_begin..]);
+
+            let (Token::BeginBlock, right_trim) = Token::from_usize_trim(id) else {
+                continue;
+            };
+            let remaining = self.source[marker_end..].trim_start();
+            let word_len = remaining
+                .find(|c| !is_ident_continue(c))
+                .unwrap_or(remaining.len());
+            let (word, after) = remaining.split_at(word_len);
+            if OPENING_KEYWORDS.contains(&word) {
+                nested += 1;
+                continue;
+            }
+            if word != "end" {
+                continue;
+            }
+            if nested > 0 {
+                nested -= 1;
+                continue;
+            }
+            let end_begin = self.source.len() - after.trim_start().len();
+            let Some((end_id, end_end)) = self.finder.starts(self.source, end_begin) else {
+                continue;
+            };
+            let (Token::EndBlock, left_trim) = Token::from_usize_trim(end_id) else {
+                continue;
+            };
+
+            let mut region: Region = (self.cursor..marker_begin).into();
+            if self.left_trim {
+                let text = region.literal(self.source);
+                region.begin += text.len() - text.trim_start().len();
+            }
+            if right_trim {
+                region.end = region.begin + region.literal(self.source).trim_end().len();
+            }
+            self.cursor = end_end;
+            self.left_trim = left_trim;
+
+            return Ok(region);
+        }
+
+        Err(Error::build(INVALID_SYNTAX)
+            .with_pointer(self.source, raw)
+            .with_help(
+                "did you close the `raw` block with `end`? blocks opened inside of the `raw` \
+                block must also be closed with `end`",
+            ))
+    }
+
     /// Return the next [`Token`] and [`Region`] in [`Tag`][`CursorState::Inside`]
     /// configuration.
     ///
@@ -87,16 +178,36 @@ impl<'source> Lexer<'source> {
     ///
     /// Returns an [`Error`] when an unexpected [`Token`] is found.
     fn lex_tag(&mut self, from: usize) -> TokenResult {
+        if self.depth > 0 {
+            if let Some(token) = self.source[from..].chars().next().and_then(closing_token) {
+                return Ok(Some(self.lex_group(from, token)));
+            }
+        }
+
         match self.finder.starts(self.source, from) {
             Some((id, length)) => {
                 let (token, is_trimmed) = Token::from_usize_trim(id);
 
+                // A begin marker may also open a group, like the `((` in
+                // `((a + b) * c)`, where a value is expected.
+                let group = self.source[from..]
+                    .chars()
+                    .next()
+                    .and_then(opening_token)
+                    .filter(|_| self.expects_value(from));
+
                 match self.state {
+                    CursorState::Inside { ref end_token }
+                        if token != *end_token && group.is_some() =>
+                    {
+                        Ok(Some(self.lex_group(from, group.unwrap())))
+                    }
                     CursorState::Inside { ref end_token } => {
                         if token == *end_token {
                             self.state = CursorState::Default;
                             self.left_trim = is_trimmed;
                             self.cursor = length;
+                            self.depth = 0;
 
                             Ok(Some((token, (from..length).into())))
                         } else {
@@ -114,6 +225,15 @@ impl<'source> Lexer<'source> {
                     _ => panic!("lexer must be in tag state"),
                 }
             }
+            None if self.source[from..].starts_with(['(', ')', '[', ']', '{', '}']) => {
+                let token = self.source[from..]
+                    .chars()
+                    .next()
+                    .and_then(|c| opening_token(c).or_else(|| closing_token(c)))
+                    .unwrap();
+
+                Ok(Some(self.lex_group(from, token)))
+            }
             None => {
                 let mut advance = |length: usize, data: Token| {
                     self.cursor += length;
@@ -131,9 +251,14 @@ impl<'source> Lexer<'source> {
                     '+' => advance(1, Token::Operator(Operator::Add)),
                     '/' => advance(1, Token::Operator(Operator::Divide)),
                     '-' => advance(1, Token::Operator(Operator::Subtract)),
+                    '.' if self.source[index..].starts_with("..=") => {
+                        advance(3, Token::RangeInclusive)
+                    }
+                    '.' if self.source[index..].starts_with("..") => advance(2, Token::Range),
                     '.' => advance(1, Token::Period),
                     ',' => advance(1, Token::Comma),
                     ':' => advance(1, Token::Colon),
+                    '?' if self.source[index..].starts_with("??") => advance(2, Token::Fallback),
                     '"' => self.lex_string(iterator, index),
                     '=' | '!' | '>' | '<' | '|' | '&' => self.lex_operator(iterator, index, char),
                     c if c.is_whitespace() => Ok(Some(self.lex_whitespace(iterator, index))),
@@ -142,14 +267,58 @@ impl<'source> Lexer<'source> {
                     _ => Err(Error::build(UNEXPECTED_TOKEN)
                         .with_pointer(self.source, index..index + char.len_utf8())
                         .with_help(
-                            "expected one of `*`, `+`, `/`, `-`, `.`, `:`, an identifier, \
-                            an ascii digit, or beginning of a string literal marked with `\"`",
+                            "expected one of `*`, `+`, `/`, `-`, `.`, `..`, `:`, `??`, `(`, `)`, \
+                            `[`, `]`, `{`, `}`, an identifier, an ascii digit, or beginning of a \
+                            string literal marked with `\"`",
                         )),
                 }
             }
         }
     }
 
+    /// Return true if the previous [`Token`] ends a value, such as an identifier
+    /// or a closing bracket.
+    fn after_value(&self) -> bool {
+        matches!(
+            self.previous,
+            Some(
+                Token::String
+                    | Token::Number
+                    | Token::Identifier
+                    | Token::True
+                    | Token::False
+                    | Token::Null
+                    | Token::RightParen
+                    | Token::RightBracket
+                    | Token::RightBrace
+            )
+        )
+    }
+
+    /// Return true if a value may begin at the given position.
+    ///
+    /// A group may follow an identifier directly to invoke a macro, as in
+    /// `button((a))`, but not after whitespace, so the `((` in `(( name (( ))`
+    /// is an unexpected marker.
+    fn expects_value(&self, from: usize) -> bool {
+        match self.previous {
+            Some(Token::Identifier) => !self.source[..from].ends_with(char::is_whitespace),
+            _ => !self.after_value(),
+        }
+    }
+
+    /// Return a [`Token`] and [`Region`] containing an opening or closing
+    /// parenthesis, bracket or brace, and track the depth of nested groups.
+    fn lex_group(&mut self, from: usize, token: Token) -> (Token, Region) {
+        match token {
+            Token::LeftParen | Token::LeftBracket | Token::LeftBrace => self.depth += 1,
+            _ => self.depth = self.depth.saturating_sub(1),
+        }
+        self.cursor = from + 1;
+
+        (token, (from..from + 1).into())
+    }
+
     /// Return a [`Token`] and [`Region`] based on the previous character.
     ///
     /// Checks the next character via `.next` to ensure the correct `Token` is
@@ -191,12 +360,27 @@ impl<'source> Lexer<'source> {
     }
 
     /// Return a [`Token`] and [`Region`] containing [`Token::Number`].
+    ///
+    /// A period is only read as part of the number when a digit follows it,
+    /// so `0..10` is read as a [`Token::Range`] between two numbers. A number
+    /// that follows a single period is a key in a path, such as the `0` in
+    /// `items.0.name`, and never contains a period.
     fn lex_digit<T>(&mut self, mut iter: T, from: usize) -> (Token, Region)
     where
         T: Iterator<Item = (usize, char)>,
     {
+        let is_key = self.source[..from].ends_with('.') && !self.source[..from].ends_with("..");
+
         loop {
             match iter.next() {
+                Some((index, '.'))
+                    if is_key
+                        || !self.source[index + 1..].starts_with(|c: char| c.is_ascii_digit()) =>
+                {
+                    self.cursor = index;
+
+                    break (Token::Number, (from..index).into());
+                }
                 Some((index, char)) if !is_number(char) => {
                     self.cursor = index;
 
@@ -279,6 +463,10 @@ impl<'source> Lexer<'source> {
     where
         T: Iterator<Item = (usize, char)>,
     {
+        // Keywords that begin a block are only recognized as the first word of
+        // the block, and `is` only after a value, so `(( raw ))` is a variable.
+        let block_start = self.previous == Some(Token::BeginBlock);
+        let after_value = self.after_value();
         let mut check_keyword = |to: usize| {
             let range_text = self
                 .source
@@ -292,12 +480,19 @@ impl<'source> Lexer<'source> {
                 "let" => Token::Keyword(Keyword::Let),
                 "for" => Token::Keyword(Keyword::For),
                 "in" => Token::Keyword(Keyword::In),
+                "is" if after_value => Token::Keyword(Keyword::Is),
+                "macro" if block_start => Token::Keyword(Keyword::Macro),
+                "import" if block_start => Token::Keyword(Keyword::Import),
                 "include" => Token::Keyword(Keyword::Include),
                 "extends" => Token::Keyword(Keyword::Extends),
                 "block" => Token::Keyword(Keyword::Block),
+                "raw" if block_start => Token::Keyword(Keyword::Raw),
+                "break" if block_start => Token::Keyword(Keyword::Break),
+                "continue" if block_start => Token::Keyword(Keyword::Continue),
                 "end" => Token::Keyword(Keyword::End),
                 "true" => Token::True,
                 "false" => Token::False,
+                "null" => Token::Null,
                 _ => Token::Identifier,
             };
             self.cursor = to;
@@ -325,6 +520,7 @@ impl<'source> Lexer<'source> {
     ///
     /// Returns an [`Error`] when an unexpected [`Token`] is found.
     fn lex_default(&mut self, from: usize) -> TokenResult {
+        let left_trim = self.left_trim;
         let mut trim_region = |mut region_begin, mut region_end, right_trim| {
             if right_trim {
                 region_end = self.source[..region_end].trim_end().len();
@@ -353,10 +549,31 @@ impl<'source> Lexer<'source> {
                             end_token: Token::EndBlock,
                         }
                     }
+                    Token::BeginComment => {
+                        let (comment_end, end_trimmed) =
+                            find_comment_end(self.source, self.finder, marker_begin, marker_end)?;
+                        self.cursor = comment_end;
+                        // A comment is removed from the output, so a pending trim
+                        // that reaches it continues past it.
+                        let pending =
+                            left_trim && self.source[from..marker_begin].trim_start().is_empty();
+
+                        let result = if from == marker_begin {
+                            Ok(Some((Token::Comment, (marker_begin..comment_end).into())))
+                        } else {
+                            trim_region(from, marker_begin, is_trimmed)
+                        };
+                        self.left_trim = end_trimmed || pending;
+
+                        return result;
+                    }
                     _ => {
                         return Err(Error::build(UNEXPECTED_TOKEN)
                             .with_pointer(self.source, marker_begin..marker_end)
-                            .with_help("expected beginning expression or beginning block"));
+                            .with_help(
+                                "expected beginning expression, beginning block \
+                                or beginning comment",
+                            ));
                     }
                 }
 
@@ -393,11 +610,66 @@ fn is_ident_continue(c: char) -> bool {
     unicode_ident::is_xid_continue(c)
 }
 
+/// Return the [`Token`] that opens a group if the given character is `(`, `[`
+/// or `{`.
+fn opening_token(c: char) -> Option<Token> {
+    match c {
+        '(' => Some(Token::LeftParen),
+        '[' => Some(Token::LeftBracket),
+        '{' => Some(Token::LeftBrace),
+        _ => None,
+    }
+}
+
+/// Return the [`Token`] that closes a group if the given character is `)`, `]`
+/// or `}`.
+fn closing_token(c: char) -> Option<Token> {
+    match c {
+        ')' => Some(Token::RightParen),
+        ']' => Some(Token::RightBracket),
+        '}' => Some(Token::RightBrace),
+        _ => None,
+    }
+}
+
 /// Return true if the given character is a number (0-9) or a period.
 fn is_number(c: char) -> bool {
     matches!(c, '0'..='9' | '.')
 }
 
+/// Return the length in bytes of the first character in the given text.
+fn next_len(text: &str) -> usize {
+    text.chars().next().map_or(1, char::len_utf8)
+}
+
+/// Return the end position of the comment that begins with the marker at
+/// the given position, and a boolean which indicates if the end marker is
+/// whitespace trimmed.
+///
+/// Any other marker within the comment is ignored.
+///
+/// # Errors
+///
+/// Returns an [`Error`] when the comment is not closed.
+fn find_comment_end(
+    source: &str,
+    finder: &Finder,
+    marker_begin: usize,
+    marker_end: usize,
+) -> Result<(usize, bool), Error> {
+    let mut from = marker_end;
+    while let Some((id, begin, end)) = finder.next(source, from) {
+        if let (Token::EndComment, is_trimmed) = Token::from_usize_trim(id) {
+            return Ok((end, is_trimmed));
+        }
+        from = begin + next_len(&source[begin..]);
+    }
+
+    Err(Error::build(INVALID_SYNTAX)
+        .with_pointer(source, marker_begin..marker_end)
+        .with_help("did you close the comment?"))
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
@@ -484,6 +756,49 @@ mod tests {
         helper_lex_next_auto("(( 10 ))", expect);
     }
 
+    #[test]
+    fn test_lex_range() {
+        let expect = vec![
+            (Token::BeginExpression, 0..2),
+            (Token::Number, 3..6),
+            (Token::Range, 6..8),
+            (Token::Identifier, 8..9),
+            (Token::RangeInclusive, 10..13),
+            (Token::Number, 13..15),
+            (Token::EndExpression, 16..18),
+        ];
+
+        helper_lex_next_auto("(( 1.5..a ..=10 ))", expect);
+    }
+
+    #[test]
+    fn test_lex_key_digit() {
+        let expect = vec![
+            (Token::BeginExpression, 0..2),
+            (Token::Identifier, 3..8),
+            (Token::Period, 8..9),
+            (Token::Number, 9..10),
+            (Token::Period, 10..11),
+            (Token::Number, 11..12),
+            (Token::EndExpression, 13..15),
+        ];
+
+        helper_lex_next_auto("(( items.0.1 ))", expect);
+    }
+
+    #[test]
+    fn test_lex_fallback() {
+        let expect = vec![
+            (Token::BeginExpression, 0..2),
+            (Token::Identifier, 3..4),
+            (Token::Fallback, 5..7),
+            (Token::String, 8..11),
+            (Token::EndExpression, 12..14),
+        ];
+
+        helper_lex_next_auto("(( a ?? \"b\" ))", expect);
+    }
+
     #[test]
     fn test_lex_ident() {
         let expect = vec![
@@ -506,6 +821,27 @@ mod tests {
         helper_lex_next_auto("(( if ))", expect);
     }
 
+    #[test]
+    fn test_lex_keyword_contextual() {
+        let expect = vec![
+            (Token::BeginBlock, 0..2),
+            (Token::Keyword(Keyword::Raw), 3..6),
+            (Token::EndBlock, 7..9),
+            (Token::BeginExpression, 9..11),
+            (Token::Identifier, 12..15),
+            (Token::Period, 15..16),
+            (Token::Identifier, 16..22),
+            (Token::EndExpression, 23..25),
+            (Token::BeginExpression, 25..27),
+            (Token::Identifier, 28..30),
+            (Token::Keyword(Keyword::Is), 31..33),
+            (Token::Identifier, 34..41),
+            (Token::EndExpression, 42..44),
+        ];
+
+        helper_lex_next_auto("(* raw *)(( raw.import ))(( is is defined ))", expect);
+    }
+
     #[test]
     fn test_lex_string_escape() {
         let expect = vec![
@@ -528,6 +864,148 @@ mod tests {
         helper_lex_next_auto("(( \"name\" ))", expect);
     }
 
+    #[test]
+    fn test_lex_paren() {
+        let expect = vec![
+            (Token::BeginExpression, 0..2),
+            (Token::LeftParen, 3..4),
+            (Token::Identifier, 4..5),
+            (Token::Operator(Operator::Add), 6..7),
+            (Token::LeftParen, 8..9),
+            (Token::Identifier, 9..10),
+            (Token::Operator(Operator::Multiply), 11..12),
+            (Token::Identifier, 13..14),
+            (Token::RightParen, 14..15),
+            (Token::RightParen, 15..16),
+            (Token::EndExpression, 17..19),
+        ];
+
+        helper_lex_next_auto("(( (a + (b * c)) ))", expect);
+    }
+
+    #[test]
+    fn test_lex_paren_leading() {
+        let expect = vec![
+            (Token::BeginExpression, 0..2),
+            (Token::LeftParen, 3..4),
+            (Token::LeftParen, 4..5),
+            (Token::Identifier, 5..6),
+            (Token::Operator(Operator::Add), 7..8),
+            (Token::Identifier, 9..10),
+            (Token::RightParen, 10..11),
+            (Token::Operator(Operator::Multiply), 12..13),
+            (Token::Identifier, 14..15),
+            (Token::RightParen, 15..16),
+            (Token::EndExpression, 17..19),
+            (Token::BeginBlock, 19..21),
+            (Token::Keyword(Keyword::If), 22..24),
+            (Token::LeftParen, 25..26),
+            (Token::LeftParen, 26..27),
+            (Token::Identifier, 27..28),
+            (Token::RightParen, 28..29),
+            (Token::RightParen, 29..30),
+            (Token::EndBlock, 31..33),
+        ];
+
+        helper_lex_next_auto("(( ((a + b) * c) ))(* if ((x)) *)", expect);
+    }
+
+    #[test]
+    fn test_lex_group_leading_custom() {
+        let expect = vec![
+            (Token::BeginExpression, 0..2),
+            (Token::LeftBrace, 3..4),
+            (Token::LeftBrace, 4..5),
+            (Token::String, 5..8),
+            (Token::Colon, 8..9),
+            (Token::Number, 10..11),
+            (Token::RightBrace, 11..12),
+            (Token::RightBrace, 12..13),
+            (Token::EndExpression, 14..16),
+            (Token::BeginExpression, 16..18),
+            (Token::Identifier, 19..20),
+            (Token::LeftBrace, 20..21),
+        ];
+
+        let finder = Finder::new(
+            Builder::new().with_expression("{{", "}}").to_syntax(),
+            Kind::AhoCorasick,
+        );
+        let mut lexer = Lexer::new("{{ {{\"a\": 1}} }}{{ f{{ }}", &finder);
+        for (token, range) in expect {
+            assert_eq!(lexer.next(), Ok(Some((token, range.into()))))
+        }
+    }
+
+    #[test]
+    fn test_lex_collection() {
+        let expect = vec![
+            (Token::BeginExpression, 0..2),
+            (Token::LeftBrace, 3..4),
+            (Token::Identifier, 4..5),
+            (Token::Colon, 5..6),
+            (Token::LeftBracket, 7..8),
+            (Token::Number, 8..9),
+            (Token::RightBracket, 9..10),
+            (Token::RightBrace, 10..11),
+            (Token::EndExpression, 12..14),
+        ];
+
+        helper_lex_next_auto("(( {a: [1]} ))", expect);
+    }
+
+    #[test]
+    fn test_lex_comment() {
+        let expect = vec![
+            (Token::Raw, 0..6),
+            (Token::Raw, 25..31),
+            (Token::BeginExpression, 31..33),
+            (Token::Identifier, 34..38),
+            (Token::EndExpression, 39..41),
+        ];
+
+        helper_lex_next_comment("hello (# (( name )) *) #) world(( name ))", expect);
+    }
+
+    #[test]
+    fn test_lex_comment_trim() {
+        let expect = vec![(Token::Raw, 0..5), (Token::Raw, 21..26)];
+
+        helper_lex_next_comment("hello  (#- note -#)  world", expect);
+    }
+
+    #[test]
+    fn test_lex_comment_pending_trim() {
+        let expect = vec![
+            (Token::BeginBlock, 0..2),
+            (Token::Keyword(Keyword::If), 3..5),
+            (Token::Identifier, 6..7),
+            (Token::EndBlock, 8..11),
+            (Token::Raw, 13..13),
+            (Token::Raw, 22..26),
+        ];
+
+        helper_lex_next_comment("(* if x -*)  (# c #)  text", expect);
+    }
+
+    #[test]
+    fn test_lex_comment_disabled() {
+        let expect = vec![(Token::Raw, 0..16)];
+
+        helper_lex_next_auto("see issue (#42)!", expect);
+    }
+
+    #[test]
+    fn test_error_unclosed_comment() {
+        let finder = Finder::new(
+            Builder::new().with_comment("(#", "#)").to_syntax(),
+            Kind::AhoCorasick,
+        );
+        let mut lexer = Lexer::new("hello (# world", &finder);
+
+        assert!(lexer.next().is_err());
+    }
+
     #[test]
     fn test_error_multiple_opening_tags() {
         let expect = vec![
@@ -552,7 +1030,25 @@ mod tests {
     where
         T: Into<Region>,
     {
-        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        helper_lex_next_with(source, expect, Builder::new());
+    }
+
+    /// Helper function like [`helper_lex_next_auto`] that enables comments with
+    /// the `(#` and `#)` markers.
+    fn helper_lex_next_comment<T>(source: &str, expect: Vec<(Token, T)>)
+    where
+        T: Into<Region>,
+    {
+        helper_lex_next_with(source, expect, Builder::new().with_comment("(#", "#)"));
+    }
+
+    /// Helper function like [`helper_lex_next_auto`] that uses the markers of
+    /// the given [`Builder`].
+    fn helper_lex_next_with<T>(source: &str, expect: Vec<(Token, T)>, builder: Builder)
+    where
+        T: Into<Region>,
+    {
+        let finder = Finder::new(builder.to_syntax(), Kind::AhoCorasick);
         let mut lexer = Lexer::new(source, &finder);
         for (token, region) in expect {
             assert_eq!(lexer.next(), Ok(Some((token, region.into()))))
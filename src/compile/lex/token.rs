@@ -25,14 +25,26 @@ pub enum Token {
     BeginBlock,
     /// End of a block - *) by default.
     EndBlock,
+    /// Beginning of a comment, when comments are enabled.
+    BeginComment,
+    /// End of a comment, when comments are enabled.
+    EndComment,
+    /// A comment, including the markers.
+    Comment,
     /// .
     Period,
+    /// ..
+    Range,
+    /// ..=
+    RangeInclusive,
     ///
     Comma,
     /// ||
     Or,
     /// &&
     And,
+    /// ??
+    Fallback,
     /// |
     Pipe,
     /// =
@@ -41,10 +53,24 @@ pub enum Token {
     True,
     /// A boolean false.
     False,
+    /// A null value.
+    Null,
     /// !
     Exclamation,
     /// :
     Colon,
+    /// (
+    LeftParen,
+    /// )
+    RightParen,
+    /// [
+    LeftBracket,
+    /// ]
+    RightBracket,
+    /// {
+    LeftBrace,
+    /// }
+    RightBrace,
     /// A recognized keyword that begins a certain type of block.
     Keyword(Keyword),
     /// Describes an action taken on two values.
@@ -66,6 +92,10 @@ impl Token {
             Marker::EndBlock => (Self::EndBlock, false),
             Marker::BeginBlockTrim => (Self::BeginBlock, true),
             Marker::EndBlockTrim => (Self::EndBlock, true),
+            Marker::BeginComment => (Self::BeginComment, false),
+            Marker::EndComment => (Self::EndComment, false),
+            Marker::BeginCommentTrim => (Self::BeginComment, true),
+            Marker::EndCommentTrim => (Self::EndComment, true),
         }
     }
 }
@@ -82,7 +112,12 @@ impl Display for Token {
             Token::EndExpression => write!(f, "end expression"),
             Token::BeginBlock => write!(f, "begin block"),
             Token::EndBlock => write!(f, "end block"),
+            Token::BeginComment => write!(f, "begin comment"),
+            Token::EndComment => write!(f, "end comment"),
+            Token::Comment => write!(f, "comment"),
             Token::Period => write!(f, "."),
+            Token::Range => write!(f, ".."),
+            Token::RangeInclusive => write!(f, "..="),
             Token::Comma => write!(f, ","),
             Token::Assign => write!(f, "="),
             Token::Keyword(keyword) => write!(f, "{keyword}"),
@@ -90,10 +125,18 @@ impl Display for Token {
             Token::Pipe => write!(f, "|"),
             Token::Exclamation => write!(f, "!"),
             Token::Colon => write!(f, ":"),
+            Token::LeftParen => write!(f, "("),
+            Token::RightParen => write!(f, ")"),
+            Token::LeftBracket => write!(f, "["),
+            Token::RightBracket => write!(f, "]"),
+            Token::LeftBrace => write!(f, "{{"),
+            Token::RightBrace => write!(f, "}}"),
             Token::Or => write!(f, "||"),
             Token::And => write!(f, "&&"),
+            Token::Fallback => write!(f, "??"),
             Token::True => write!(f, "true"),
             Token::False => write!(f, "false"),
+            Token::Null => write!(f, "null"),
         }
     }
 }
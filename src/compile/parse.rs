@@ -4,16 +4,14 @@ pub mod tree;
 mod fragment;
 mod state;
 
+use std::collections::HashMap;
+
 use crate::{log::Error, region::Region};
 
 use super::{
     expected_operator,
     lex::{token::Token, Lexer},
-    parse::{
-        fragment::Fragment,
-        state::{BlockState, IfState},
-        tree::*,
-    },
+    parse::{fragment::Fragment, state::BlockState, tree::*},
     Keyword, Operator, Scope, Template, TokenResult, TokenResultMust, INVALID_SYNTAX,
     UNEXPECTED_TOKEN,
 };
@@ -24,6 +22,16 @@ use serde_json::{Number, Value};
 const UNEXPECTED_BLOCK: &str = "unexpected block";
 const UNEXPECTED_EOF: &str = "unexpected eof";
 
+/// The identifier that introduces the step of a [`Range`].
+const STEP: &str = "step";
+/// The identifier that begins an [`Import`] of named macros.
+const FROM: &str = "from";
+/// The identifier that introduces the namespace of an [`Import`].
+const AS: &str = "as";
+/// The identifier that introduces the macro names of an [`Import`] that
+/// begins with `from`.
+const IMPORT: &str = "import";
+
 /// Provides methods to transform an input stream of [`Token`] into an abstract
 /// syntax tree composed of [`Tree`].
 pub struct Parser<'source> {
@@ -34,6 +42,12 @@ pub struct Parser<'source> {
     /// Temporarily store an [`Extends`] for the [`Template`] that is being
     /// parsed.
     extended: Option<Extends>,
+    /// Temporarily store each [`Macro`] for the [`Template`] that is being
+    /// parsed.
+    macros: HashMap<String, Macro>,
+    /// Temporarily store each [`Import`] for the [`Template`] that is being
+    /// parsed.
+    imports: Vec<Import>,
 }
 
 impl<'source> Parser<'source> {
@@ -44,6 +58,8 @@ impl<'source> Parser<'source> {
             lexer: Lexer::new(source, finder),
             buffer: None,
             extended: None,
+            macros: HashMap::new(),
+            imports: vec![],
         }
     }
 
@@ -83,17 +99,17 @@ impl<'source> Parser<'source> {
                     let end = self.next_must(Token::EndBlock)?.1.combine(region);
 
                     match fragment {
-                        Fragment::If(tr) => {
+                        Fragment::If(condition) => {
                             states.push(BlockState::If {
                                 else_if: false,
-                                tree: tr,
+                                condition,
                                 region: end,
                                 has_else: false,
                             });
                             scopes.push(Scope::new());
                             continue;
                         }
-                        Fragment::ElseIf(tr) => {
+                        Fragment::ElseIf(condition) => {
                             let error = || {
                                 Error::build(UNEXPECTED_BLOCK)
                                     .with_pointer(self.lexer.source, end)
@@ -110,7 +126,7 @@ impl<'source> Parser<'source> {
 
                             states.push(BlockState::If {
                                 else_if: true,
-                                tree: tr,
+                                condition,
                                 region: end,
                                 has_else: false,
                             });
@@ -123,13 +139,17 @@ impl<'source> Parser<'source> {
                             let error = || {
                                 Error::build(UNEXPECTED_BLOCK)
                                     .with_pointer(self.lexer.source, end)
-                                    .with_help("expected `if` before `else`")
+                                    .with_help("expected `if` or `for` before `else`")
                             };
 
                             match states.last_mut().ok_or_else(error)? {
                                 BlockState::If {
                                     has_else: has_else @ false,
                                     ..
+                                }
+                                | BlockState::For {
+                                    has_else: has_else @ false,
+                                    ..
                                 } => *has_else = true,
                                 _ => return Err(error()),
                             }
@@ -138,7 +158,12 @@ impl<'source> Parser<'source> {
                             continue;
                         }
                         Fragment::For(set, base) => {
-                            states.push(BlockState::For { set, base, region });
+                            states.push(BlockState::For {
+                                set,
+                                base,
+                                region,
+                                has_else: false,
+                            });
                             scopes.push(Scope::new());
                             continue;
                         }
@@ -167,6 +192,52 @@ impl<'source> Parser<'source> {
                             scopes.push(Scope::new());
                             continue;
                         }
+                        Fragment::Macro(name, parameters) => {
+                            if !states.is_empty() {
+                                return Err(Error::build(UNEXPECTED_BLOCK)
+                                    .with_pointer(self.lexer.source, end)
+                                    .with_help(
+                                        "block `macro` must appear at top level of template",
+                                    ));
+                            }
+
+                            states.push(BlockState::Macro {
+                                name,
+                                parameters,
+                                region: end,
+                            });
+                            scopes.push(Scope::new());
+                            continue;
+                        }
+                        Fragment::Import(name, binding) => {
+                            if !states.is_empty() {
+                                return Err(Error::build(UNEXPECTED_BLOCK)
+                                    .with_pointer(self.lexer.source, end)
+                                    .with_help("`import` must appear at top level of template"));
+                            }
+
+                            self.insert_import(Import {
+                                name,
+                                binding,
+                                region: end,
+                            })?;
+                            continue;
+                        }
+                        Fragment::Raw => Tree::Raw(self.lexer.lex_raw(end)?),
+                        fragment @ (Fragment::Break | Fragment::Continue) => {
+                            if !is_in_loop(&states) {
+                                return Err(Error::build(UNEXPECTED_BLOCK)
+                                    .with_pointer(self.lexer.source, end)
+                                    .with_help(format!(
+                                        "`{fragment}` must appear inside of a `for` block"
+                                    )));
+                            }
+
+                            match fragment {
+                                Fragment::Break => Tree::Break,
+                                _ => Tree::Continue,
+                            }
+                        }
                         Fragment::End => match states.last() {
                             Some(parent) => match parent {
                                 BlockState::If { .. } => loop {
@@ -175,14 +246,14 @@ impl<'source> Parser<'source> {
                                                 .with_help("`if` block does not appear to have a beginning"))? {
                                             BlockState::If {
                                                 else_if,
-                                                tree,
+                                                condition,
                                                 region,
                                                 has_else,
                                             } => {
                                                 let else_branch = has_else.then(|| scopes.pop().unwrap());
                                                 let then_branch = scopes.pop().unwrap();
                                                 let tree = Tree::If(If {
-                                                    tree,
+                                                    condition,
                                                     else_branch,
                                                     then_branch,
                                                     region: end.combine(region),
@@ -200,12 +271,21 @@ impl<'source> Parser<'source> {
                                         }
                                 },
                                 BlockState::For { .. } => match states.pop().unwrap() {
-                                    BlockState::For { set, base, region } => Tree::For(For {
+                                    BlockState::For {
                                         set,
                                         base,
-                                        scope: scopes.pop().unwrap(),
-                                        region: end.combine(region),
-                                    }),
+                                        region,
+                                        has_else,
+                                    } => {
+                                        let else_scope = has_else.then(|| scopes.pop().unwrap());
+                                        Tree::For(For {
+                                            set,
+                                            base,
+                                            scope: scopes.pop().unwrap(),
+                                            else_scope,
+                                            region: end.combine(region),
+                                        })
+                                    }
                                     _ => unreachable!(),
                                 },
                                 BlockState::Block { .. } => match states.pop().unwrap() {
@@ -216,6 +296,19 @@ impl<'source> Parser<'source> {
                                     }),
                                     _ => unreachable!(),
                                 },
+                                BlockState::Macro { .. } => match states.pop().unwrap() {
+                                    BlockState::Macro {
+                                        name, parameters, ..
+                                    } => {
+                                        self.insert_macro(Macro {
+                                            name,
+                                            parameters,
+                                            scope: scopes.pop().unwrap(),
+                                        })?;
+                                        continue;
+                                    }
+                                    _ => unreachable!(),
+                                },
                             },
                             None => {
                                 return Err(Error::build(
@@ -236,6 +329,7 @@ impl<'source> Parser<'source> {
                 BlockState::If { region, .. } => ("if", region),
                 BlockState::For { region, .. } => ("for", region),
                 BlockState::Block { region, .. } => ("block", region),
+                BlockState::Macro { region, .. } => ("macro", region),
             };
 
             return Err(Error::build(INVALID_SYNTAX)
@@ -249,6 +343,8 @@ impl<'source> Parser<'source> {
             scopes.remove(0),
             self.lexer.source.to_owned(),
             self.extended,
+            self.macros,
+            self.imports,
         ))
     }
 
@@ -265,18 +361,24 @@ impl<'source> Parser<'source> {
         //   from                to
         //   |                   |
         // (* if name == "taylor" *)
+        if let Some((Token::Identifier, region)) = self.peek()? {
+            if &self.lexer.source[region] == FROM {
+                self.next_must(Token::Identifier)?;
+                return self.parse_import_from();
+            }
+        }
         let (keyword, region) = self.parse_keyword()?;
 
         match keyword {
             Keyword::If => {
-                let tree = self.parse_tree()?;
-                Ok(Fragment::If(tree))
+                let condition = self.parse_condition()?;
+                Ok(Fragment::If(condition))
             }
             Keyword::Else => {
                 if self.peek_is(Token::Keyword(Keyword::If))? {
                     self.next_must(Token::Keyword(Keyword::If))?;
-                    let tree = self.parse_tree()?;
-                    Ok(Fragment::ElseIf(tree))
+                    let condition = self.parse_condition()?;
+                    Ok(Fragment::ElseIf(condition))
                 } else {
                     Ok(Fragment::Else)
                 }
@@ -306,13 +408,72 @@ impl<'source> Parser<'source> {
                 let name = self.parse_base()?;
                 Ok(Fragment::Block(name))
             }
+            Keyword::Macro => {
+                let name = self.parse_identifier()?;
+                let parameters = self.parse_parameters()?;
+                Ok(Fragment::Macro(name, parameters))
+            }
+            Keyword::Import => {
+                let name = self.parse_base()?;
+                match self.next_must(Token::Identifier)? {
+                    (_, region) if &self.lexer.source[region] == AS => {}
+                    (_, region) => {
+                        return Err(Error::build(UNEXPECTED_TOKEN)
+                            .with_pointer(self.lexer.source, region)
+                            .with_help(
+                                "expected `as` and a namespace after the name of the template, \
+                                like `import \"forms.html\" as forms`",
+                            ))
+                    }
+                }
+                let namespace = self.parse_identifier()?;
+                Ok(Fragment::Import(name, Binding::Namespace(namespace)))
+            }
+            Keyword::Raw => Ok(Fragment::Raw),
+            Keyword::Break => Ok(Fragment::Break),
+            Keyword::Continue => Ok(Fragment::Continue),
             Keyword::End => Ok(Fragment::End),
-            k @ Keyword::Not | k @ Keyword::In => Err(Error::build(UNEXPECTED_TOKEN)
-                .with_pointer(self.lexer.source, region)
-                .with_help(format!("keyword `{k}` is not valid in this position"))),
+            k @ Keyword::Not | k @ Keyword::In | k @ Keyword::Is => {
+                Err(Error::build(UNEXPECTED_TOKEN)
+                    .with_pointer(self.lexer.source, region)
+                    .with_help(format!("keyword `{k}` is not valid in this position")))
+            }
         }
     }
 
+    /// Parse the remainder of an [`Import`] that begins with `from`, which lists
+    /// the names of the imported macros.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
+    /// instances are left.
+    fn parse_import_from(&mut self) -> Result<Fragment, Error> {
+        // (* from "forms.html" import input, select *)
+        //         ^                                ^
+        //         from                             to
+        let name = self.parse_base()?;
+        match self.next_must(Token::Identifier)? {
+            (_, region) if &self.lexer.source[region] == IMPORT => {}
+            (_, region) => {
+                return Err(Error::build(UNEXPECTED_TOKEN)
+                    .with_pointer(self.lexer.source, region)
+                    .with_help(
+                        "expected `import` and the names of macros after the name of the \
+                        template, like `from \"forms.html\" import input`",
+                    ))
+            }
+        }
+
+        let mut macros = vec![self.parse_identifier()?];
+        while self.peek_is(Token::Comma)? {
+            self.next_must(Token::Comma)?;
+            macros.push(self.parse_identifier()?);
+        }
+
+        Ok(Fragment::Import(name, Binding::Macros(macros)))
+    }
+
     /// Parse a [`Mount`].
     ///
     /// Similar to `.parse_arguments`, but requires that all arguments are named.
@@ -365,6 +526,124 @@ impl<'source> Parser<'source> {
         Ok(Some(Mount { values, region }))
     }
 
+    /// Parse the [`Parameter`] instances of a [`Macro`], within parentheses.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when an unexpected [`Token`] is found, a parameter
+    /// name is repeated, or a `Parameter` without a default value follows one
+    /// with a default value.
+    fn parse_parameters(&mut self) -> Result<Vec<Parameter>, Error> {
+        // (* macro button(label, kind="primary") *)
+        //                ^                     ^
+        //                from                  to
+        let mut parameters: Vec<Parameter> = vec![];
+        self.next_must(Token::LeftParen)?;
+
+        loop {
+            if self.peek_is(Token::RightParen)? {
+                self.next_must(Token::RightParen)?;
+                break;
+            }
+            let name = self.parse_identifier()?;
+            let literal = name.region.literal(self.lexer.source);
+            if parameters
+                .iter()
+                .any(|parameter| parameter.name.region.literal(self.lexer.source) == literal)
+            {
+                return Err(Error::build(INVALID_SYNTAX)
+                    .with_pointer(self.lexer.source, name.region)
+                    .with_help(format!("parameter `{literal}` is repeated")));
+            }
+
+            let default = if self.peek_is(Token::Assign)? {
+                self.next_must(Token::Assign)?;
+                Some(self.parse_base()?)
+            } else if parameters
+                .iter()
+                .any(|parameter| parameter.default.is_some())
+            {
+                return Err(Error::build(INVALID_SYNTAX)
+                    .with_pointer(self.lexer.source, name.region)
+                    .with_help(format!(
+                        "parameter `{literal}` must have a default value, because it follows \
+                        a parameter with a default value"
+                    )));
+            } else {
+                None
+            };
+            parameters.push(Parameter { name, default });
+
+            match self.next_any_must()? {
+                (Token::Comma, _) => continue,
+                (Token::RightParen, _) => break,
+                (token, region) => {
+                    return Err(Error::build(UNEXPECTED_TOKEN)
+                        .with_pointer(self.lexer.source, region)
+                        .with_help(format!(
+                            "expected `,` or `)` after parameter, found `{token}`"
+                        )))
+                }
+            }
+        }
+
+        Ok(parameters)
+    }
+
+    /// Store an [`Import`] for the [`Template`] that is being parsed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if a name made available by the `Import` was already
+    /// made available by an earlier `Import`.
+    fn insert_import(&mut self, import: Import) -> Result<(), Error> {
+        let source = self.lexer.source;
+        let bound = |import: &Import| -> Vec<Identifier> {
+            match &import.binding {
+                Binding::Namespace(namespace) => vec![namespace.clone()],
+                Binding::Macros(macros) => macros.clone(),
+            }
+        };
+
+        let mut seen: Vec<&str> = self
+            .imports
+            .iter()
+            .flat_map(bound)
+            .map(|identifier| identifier.region.literal(source))
+            .collect();
+        for identifier in bound(&import) {
+            let name = identifier.region.literal(source);
+            if seen.contains(&name) {
+                return Err(Error::build(INVALID_SYNTAX)
+                    .with_pointer(source, identifier.region)
+                    .with_help(format!("`{name}` is already imported in this template")));
+            }
+            seen.push(name);
+        }
+        self.imports.push(import);
+
+        Ok(())
+    }
+
+    /// Store a [`Macro`] for the [`Template`] that is being parsed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if a `Macro` with the same name was already defined.
+    fn insert_macro(&mut self, definition: Macro) -> Result<(), Error> {
+        let name = definition.name.region.literal(self.lexer.source);
+        if self.macros.contains_key(name) {
+            return Err(Error::build(INVALID_SYNTAX)
+                .with_pointer(self.lexer.source, definition.name.region)
+                .with_help(format!(
+                    "macro `{name}` is already defined in this template"
+                )));
+        }
+        self.macros.insert(name.to_string(), definition);
+
+        Ok(())
+    }
+
     /// Parse an [`Expression`].
     ///
     /// An `Expression` is a call to render some kind of data,
@@ -403,97 +682,194 @@ impl<'source> Parser<'source> {
         Ok(expression)
     }
 
-    /// Parse an [`IfTree`].
+    /// Parse a [`Condition`].
     ///
-    /// This `IfTree` will contain all of the information necessary to determine if the
-    /// block should pass.
+    /// This `Condition` will contain all of the information necessary to determine if
+    /// the block should pass. The `Condition` ends before the end of the block, or any
+    /// other [`Token`] that may follow a [`Conditional`], such as an `else` keyword.
     ///
     /// # Errors
     ///
     /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
     /// instances are left.
-    fn parse_tree(&mut self) -> Result<IfTree, Error> {
-        // this >= that && these == those || a == b *)
-        // |-----------    --------------    ------ to
-        // from  |                  |            |
-        //       negatable          negatable    negatable
-        let mut tree = IfTree::new();
-        let mut state = IfState::default();
+    fn parse_condition(&mut self) -> Result<Condition, Error> {
+        // this >= that && (these == those || not is_admin) *)
+        // ------------    ----------------------------------- to
+        // |          |    |
+        // from       and  group
+        let mut left = self.parse_and()?;
+
+        while self.peek_is(Token::Or)? {
+            self.next_must(Token::Or)?;
+            let right = self.parse_and()?;
+            left = Condition::Or(Box::new(left), Box::new(right));
+        }
 
-        loop {
-            match state {
-                IfState::Base(has_left) => {
-                    if has_left {
-                        let base = self.parse_base()?;
-                        tree.last_leaf_mut_must("has_left implies that a leaf exists")
-                            .right = Some(base);
-
-                        state = IfState::Transition;
-                    } else {
-                        let (base, negated) = if self.peek_is(Token::Keyword(Keyword::Not))? {
-                            self.next_must(Token::Keyword(Keyword::Not))?;
-
-                            (self.parse_base()?, true)
-                        } else {
-                            (self.parse_base()?, false)
-                        };
-                        tree.last_mut_must().push(IfLeaf::new(base, negated));
+        Ok(left)
+    }
 
-                        state = IfState::Operator;
-                    };
-                }
-                IfState::Operator => match self.peek_must()? {
-                    (token, region) => match token {
-                        Token::EndBlock | Token::Or | Token::And => state = IfState::Transition,
-                        Token::Operator(op) => {
-                            self.next_must(Token::Operator(op))?;
-                            tree.last_leaf_mut_must("operator state implies that leaf exists")
-                                .operator = Some(op);
-                            state = IfState::Base(true);
-                        }
-                        unexpected => {
-                            return Err(Error::build(UNEXPECTED_TOKEN)
-                                .with_pointer(self.lexer.source, region)
-                                .with_help(expected_operator(unexpected)))
+    /// Parse a [`Condition`] made of any [`Condition::And`] instances, which bind
+    /// more tightly than [`Condition::Or`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
+    /// instances are left.
+    fn parse_and(&mut self) -> Result<Condition, Error> {
+        let mut left = self.parse_not()?;
+
+        while self.peek_is(Token::And)? {
+            self.next_must(Token::And)?;
+            let right = self.parse_not()?;
+            left = Condition::And(Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    /// Parse a [`Condition`] that may be negated with the `not` keyword.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
+    /// instances are left.
+    fn parse_not(&mut self) -> Result<Condition, Error> {
+        if self.peek_is(Token::Keyword(Keyword::Not))? {
+            self.next_must(Token::Keyword(Keyword::Not))?;
+
+            return Ok(Condition::Not(Box::new(self.parse_not()?)));
+        }
+
+        self.parse_comparison()
+    }
+
+    /// Parse a [`Condition`] within parentheses, a [`Comparison`], or a [`Base`]
+    /// that is checked for truthiness.
+    ///
+    /// Parentheses that contain only a `Base`, such as `(a + b) > c`, are treated
+    /// as part of that `Base`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
+    /// instances are left.
+    fn parse_comparison(&mut self) -> Result<Condition, Error> {
+        let left = match self.peek_must()? {
+            (Token::LeftParen, begin) => {
+                self.next_must(Token::LeftParen)?;
+                let condition = self.parse_condition()?;
+                let (_, end) = self.next_must(Token::RightParen)?;
+
+                match condition {
+                    Condition::Truthy(mut base) => {
+                        if let Base::Arithmetic(arithmetic) = &mut base {
+                            arithmetic.region = begin.combine(end);
                         }
-                    },
-                },
-                IfState::Transition => match self.peek_must()? {
-                    (Token::EndBlock, _) => break,
-                    (Token::Or, _) => {
-                        self.next_must(Token::Or)?;
-
-                        tree.split_branch();
-                        state = IfState::Base(false);
-                    }
-                    (Token::And, _) => {
-                        self.next_must(Token::And)?;
-
-                        let (base, negated) = if self.peek_is(Token::Keyword(Keyword::Not))? {
-                            self.next_must(Token::Keyword(Keyword::Not))?;
-                            (self.parse_base()?, true)
-                        } else {
-                            (self.parse_base()?, false)
-                        };
 
-                        tree.split_leaf(IfLeaf::new(base, negated));
-                        state = IfState::Operator;
+                        self.parse_arithmetic_from(base, 0)?
                     }
-                    unexpected => {
-                        return Err(Error::build(UNEXPECTED_TOKEN)
-                            .with_pointer(self.lexer.source, unexpected.1)
-                            .with_help(format!(
-                                "expected `{}`, `{}` or end of block, found `{}`",
-                                Token::And,
-                                Token::Or,
-                                unexpected.0
-                            )))
+                    condition => {
+                        self.expect_condition_end()?;
+
+                        return Ok(condition);
                     }
-                },
+                }
             }
-        }
+            _ => self.parse_base()?,
+        };
 
-        Ok(tree)
+        let condition = match self.peek_must()? {
+            (Token::Operator(operator), _) => {
+                self.next_must(Token::Operator(operator))?;
+                let right = self.parse_base()?;
+                self.expect_condition_end()?;
+
+                Condition::Compare(Comparison {
+                    left,
+                    operator,
+                    right,
+                })
+            }
+            (Token::Keyword(keyword @ (Keyword::In | Keyword::Not)), _) => {
+                self.next_must(Token::Keyword(keyword))?;
+                let operator = if keyword == Keyword::Not {
+                    self.next_must(Token::Keyword(Keyword::In))?;
+                    Operator::NotIn
+                } else {
+                    Operator::In
+                };
+                let right = self.parse_base()?;
+                self.expect_condition_end()?;
+
+                Condition::Compare(Comparison {
+                    left,
+                    operator,
+                    right,
+                })
+            }
+            (Token::Keyword(Keyword::Is), _) => {
+                self.next_must(Token::Keyword(Keyword::Is))?;
+                let negated = self.peek_is(Token::Keyword(Keyword::Not))?;
+                if negated {
+                    self.next_must(Token::Keyword(Keyword::Not))?;
+                }
+                // The `null` keyword doubles as the name of the `null` test.
+                let name = if self.peek_is(Token::Null)? {
+                    let (_, region) = self.next_must(Token::Null)?;
+                    Identifier { region }
+                } else {
+                    self.parse_identifier()?
+                };
+                let arguments = self.parse_arguments()?;
+                self.expect_condition_end()?;
+
+                let end = arguments
+                    .as_ref()
+                    .map_or(name.region, |arguments| arguments.region);
+                let check = Condition::Check(Check {
+                    region: left.get_region().combine(end),
+                    base: left,
+                    name,
+                    arguments,
+                });
+
+                if negated {
+                    Condition::Not(Box::new(check))
+                } else {
+                    check
+                }
+            }
+            (Token::Or | Token::And, _) => Condition::Truthy(left),
+            (token, _) if is_condition_end(token) => Condition::Truthy(left),
+            (unexpected, region) => {
+                return Err(Error::build(UNEXPECTED_TOKEN)
+                    .with_pointer(self.lexer.source, region)
+                    .with_help(expected_operator(unexpected)))
+            }
+        };
+
+        Ok(condition)
+    }
+
+    /// Ensure that the next [`Token`] may follow a complete [`Comparison`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the next `Token` is not `&&`, `||`, or a `Token`
+    /// that ends a [`Condition`].
+    fn expect_condition_end(&mut self) -> Result<(), Error> {
+        match self.peek_must()? {
+            (Token::Or | Token::And, _) => Ok(()),
+            (token, _) if is_condition_end(token) => Ok(()),
+            (unexpected, region) => Err(Error::build(UNEXPECTED_TOKEN)
+                .with_pointer(self.lexer.source, region)
+                .with_help(format!(
+                    "expected `{}`, `{}` or end of block, found `{}`",
+                    Token::And,
+                    Token::Or,
+                    unexpected
+                ))),
+        }
     }
 
     /// Parse a [`Set`].
@@ -529,7 +905,8 @@ impl<'source> Parser<'source> {
             (token, region) => Err(Error::build(UNEXPECTED_TOKEN)
                 .with_help(format!(
                     "expected keyword like `if`, `else`, `let`, `for`, `in`, `include`, \
-                    `extends`, `block`, `end`, found `{token}`"
+                    `extends`, `block`, `macro`, `import`, `raw`, `break`, `continue`, `end`, \
+                    found `{token}`"
                 ))
                 .with_pointer(self.lexer.source, region)),
         }
@@ -549,12 +926,14 @@ impl<'source> Parser<'source> {
         //                         from         to       from     to
         let mut values: Vec<Argument> = vec![];
 
-        // Expect arguments until `Token::Pipe` or `Token::End*`.
-        while !self.peek_is(Token::Pipe)?
-            && !self.peek_is(Token::EndExpression)?
-            && !self.peek_is(Token::EndBlock)?
-        {
-            values.push(self.parse_argument()?);
+        // Expect arguments until `Token::Pipe`, `Token::End*`, or another `Token`
+        // that ends the `Condition` of a `Check`.
+        loop {
+            match self.peek_must()? {
+                (Token::Or | Token::And, _) => break,
+                (token, _) if is_condition_end(token) => break,
+                _ => values.push(self.parse_argument()?),
+            }
             if !self.peek_is(Token::Comma)? {
                 break;
             }
@@ -612,12 +991,169 @@ impl<'source> Parser<'source> {
 
     /// Parse a [`Base`].
     ///
+    /// The `Base` may be an [`Arithmetic`] made up of other `Base` instances, such as
+    /// `price * (quantity + 1)`, a [`Range`] such as `0..count step 2`, or a
+    /// [`Conditional`] such as `"active" if selected else ""`.
+    ///
     /// # Errors
     ///
     /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
     /// instances are left.
     fn parse_base(&mut self) -> Result<Base, Error> {
+        let base = self.parse_range()?;
+        if !self.peek_is(Token::Keyword(Keyword::If))? {
+            return Ok(base);
+        }
+        self.next_must(Token::Keyword(Keyword::If))?;
+
+        // "active" if selected && enabled else ""
+        // |        --------------------      |
+        // from     condition                 to
+        let condition = self.parse_condition()?;
+        let else_base = if self.peek_is(Token::Keyword(Keyword::Else))? {
+            self.next_must(Token::Keyword(Keyword::Else))?;
+            Some(Box::new(self.parse_base()?))
+        } else {
+            None
+        };
+        let end = match &else_base {
+            Some(else_base) => else_base.get_region(),
+            None => condition.get_region(),
+        };
+
+        Ok(Base::Conditional(Conditional {
+            region: base.get_region().combine(end),
+            then_base: Box::new(base),
+            condition: Box::new(condition),
+            else_base,
+        }))
+    }
+
+    /// Parse a [`Base`], which may be a [`Range`] such as `0..count step 2`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
+    /// instances are left.
+    fn parse_range(&mut self) -> Result<Base, Error> {
+        let start = self.parse_fallback()?;
+
+        let inclusive = match self.peek()? {
+            Some((Token::Range, _)) => false,
+            Some((Token::RangeInclusive, _)) => true,
+            _ => return Ok(start),
+        };
+        self.next_any_must()?;
+        let end = self.parse_fallback()?;
+
+        let step = match self.peek()? {
+            Some((Token::Identifier, region)) if &self.lexer.source[region] == STEP => {
+                self.next_must(Token::Identifier)?;
+                Some(Box::new(self.parse_fallback()?))
+            }
+            _ => None,
+        };
+        let region = start.get_region().combine(
+            step.as_ref()
+                .map_or(end.get_region(), |step| step.get_region()),
+        );
+
+        Ok(Base::Range(Range {
+            start: Box::new(start),
+            end: Box::new(end),
+            step,
+            inclusive,
+            region,
+        }))
+    }
+
+    /// Parse a [`Base`], and any [`Fallback`] instances that follow it, such as
+    /// `user.nickname ?? user.name ?? "anon"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
+    /// instances are left.
+    fn parse_fallback(&mut self) -> Result<Base, Error> {
+        let left = self.parse_arithmetic(0)?;
+        if !self.peek_is(Token::Fallback)? {
+            return Ok(left);
+        }
+        self.next_must(Token::Fallback)?;
+
+        let right = self.parse_fallback()?;
+        let region = left.get_region().combine(right.get_region());
+
+        Ok(Base::Fallback(Fallback {
+            left: Box::new(left),
+            right: Box::new(right),
+            region,
+        }))
+    }
+
+    /// Parse a [`Base`], and any arithmetic [`Operator`] that follows with a
+    /// precedence of at least `minimum`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
+    /// instances are left.
+    fn parse_arithmetic(&mut self, minimum: u8) -> Result<Base, Error> {
+        let left = self.parse_primary()?;
+
+        self.parse_arithmetic_from(left, minimum)
+    }
+
+    /// Parse any arithmetic [`Operator`] that follows the given [`Base`] with a
+    /// precedence of at least `minimum`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
+    /// instances are left.
+    fn parse_arithmetic_from(&mut self, mut left: Base, minimum: u8) -> Result<Base, Error> {
+        // price + tax * quantity
+        // |       ----------- bound first, higher precedence
+        // from                |
+        //                     to
+
+        while let Some((Token::Operator(operator), _)) = self.peek()? {
+            let precedence = match operator.get_precedence() {
+                Some(precedence) if precedence >= minimum => precedence,
+                _ => break,
+            };
+            self.next_must(Token::Operator(operator))?;
+
+            let right = self.parse_arithmetic(precedence + 1)?;
+            left = Base::Arithmetic(Arithmetic::new(left, operator, right));
+        }
+
+        Ok(left)
+    }
+
+    /// Parse a [`Base`] that is not followed by an arithmetic [`Operator`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
+    /// instances are left.
+    fn parse_primary(&mut self) -> Result<Base, Error> {
         let expression = match self.next_any_must()? {
+            (Token::LeftParen, region) => {
+                // A group may hold any base, such as `(name ?? "") + "!"`.
+                let mut base = self.parse_base()?;
+                let (_, end) = self.next_must(Token::RightParen)?;
+                match &mut base {
+                    Base::Arithmetic(arithmetic) => arithmetic.region = region.combine(end),
+                    Base::Fallback(fallback) => fallback.region = region.combine(end),
+                    Base::Conditional(conditional) => conditional.region = region.combine(end),
+                    _ => {}
+                }
+
+                base
+            }
+            (Token::LeftBracket, region) => self.parse_array(region)?,
+            (Token::LeftBrace, region) => self.parse_object(region)?,
             (Token::False, region) => Base::Literal(Literal {
                 value: Value::Bool(false),
                 region,
@@ -626,6 +1162,10 @@ impl<'source> Parser<'source> {
                 value: Value::Bool(true),
                 region,
             }),
+            (Token::Null, region) => Base::Literal(Literal {
+                value: Value::Null,
+                region,
+            }),
             (Token::Operator(operator), region) => match operator {
                 Operator::Add | Operator::Subtract => {
                     let (_, next_region) = self.next_must(Token::Number)?;
@@ -662,16 +1202,28 @@ impl<'source> Parser<'source> {
                 Base::Literal(literal)
             }
             (Token::Identifier, region) => {
-                let mut path = vec![Identifier { region }];
+                let mut path = vec![Key::Identifier(Identifier { region })];
 
-                while self.peek_is(Token::Period)? {
-                    self.next_must(Token::Period)?;
-                    path.push(self.parse_key()?);
+                loop {
+                    match self.peek()? {
+                        Some((Token::Period, _)) => {
+                            self.next_must(Token::Period)?;
+                            path.push(self.parse_key()?);
+                        }
+                        Some((Token::LeftBracket, begin)) => {
+                            self.next_must(Token::LeftBracket)?;
+                            path.push(self.parse_subscript(begin)?);
+                        }
+                        Some((Token::LeftParen, begin)) => {
+                            self.next_must(Token::LeftParen)?;
+                            return self.parse_invoke(Variable::new(path), begin);
+                        }
+                        _ => break,
+                    }
                 }
                 Base::Variable(Variable::new(path))
             }
             (token, region) => {
-                println!("{}", token);
                 return Err(Error::build(UNEXPECTED_TOKEN)
                     .with_pointer(self.lexer.source, region)
                     .with_help(format!(
@@ -684,6 +1236,163 @@ impl<'source> Parser<'source> {
         Ok(expression)
     }
 
+    /// Parse an [`Invoke`] of the named [`Macro`], with arguments that begin
+    /// after the parenthesis at the given [`Region`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
+    /// instances are left.
+    fn parse_invoke(&mut self, name: Variable, begin: Region) -> Result<Base, Error> {
+        // button("Save", kind: "secondary")
+        // |                               |
+        // from                            to
+        let mut arguments = vec![];
+
+        let end = loop {
+            if let Some((Token::RightParen, end)) = self.peek()? {
+                self.next_must(Token::RightParen)?;
+                break end;
+            }
+            arguments.push(self.parse_argument()?);
+
+            match self.next_any_must()? {
+                (Token::Comma, _) => continue,
+                (Token::RightParen, end) => break end,
+                (token, region) => {
+                    return Err(Error::build(UNEXPECTED_TOKEN)
+                        .with_pointer(self.lexer.source, region)
+                        .with_help(format!(
+                            "expected `,` or `)` after argument to macro, found `{token}`"
+                        )))
+                }
+            }
+        };
+        let region = name.get_region().combine(begin).combine(end);
+
+        Ok(Base::Invoke(Invoke {
+            name,
+            arguments,
+            region,
+        }))
+    }
+
+    /// Parse an [`Array`] that begins with the bracket at the given [`Region`].
+    ///
+    /// When every value is a [`Literal`], a `Literal` containing a [`Value::Array`]
+    /// is returned instead, so it does not need to be evaluated at render time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
+    /// instances are left.
+    fn parse_array(&mut self, begin: Region) -> Result<Base, Error> {
+        // [1, name, "three"]
+        // |                |
+        // from             to
+        let mut values = vec![];
+
+        let end = loop {
+            if let Some((Token::RightBracket, end)) = self.peek()? {
+                self.next_must(Token::RightBracket)?;
+                break end;
+            }
+            values.push(self.parse_base()?);
+
+            match self.next_any_must()? {
+                (Token::Comma, _) => continue,
+                (Token::RightBracket, end) => break end,
+                (token, region) => {
+                    return Err(Error::build(UNEXPECTED_TOKEN)
+                        .with_pointer(self.lexer.source, region)
+                        .with_help(format!(
+                            "expected `,` or `]` after value in array, found `{token}`"
+                        )))
+                }
+            }
+        };
+        let region = begin.combine(end);
+
+        if values.iter().all(|value| matches!(value, Base::Literal(_))) {
+            let values = values.into_iter().map(|value| match value {
+                Base::Literal(literal) => literal.value,
+                _ => unreachable!(),
+            });
+
+            return Ok(Base::Literal(Literal::new(
+                Value::Array(values.collect()),
+                region,
+            )));
+        }
+
+        Ok(Base::Array(Array { values, region }))
+    }
+
+    /// Parse an [`Object`] that begins with the brace at the given [`Region`].
+    ///
+    /// Each key may be an unquoted identifier or a string literal. When every
+    /// value is a [`Literal`], a `Literal` containing a [`Value::Object`] is
+    /// returned instead, so it does not need to be evaluated at render time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
+    /// instances are left.
+    fn parse_object(&mut self, begin: Region) -> Result<Base, Error> {
+        // { title: page.title, "class name": "card" }
+        // |                                         |
+        // from                                      to
+        let mut pairs = vec![];
+
+        let end = loop {
+            let key = match self.next_any_must()? {
+                (Token::RightBrace, end) => break end,
+                (Token::Identifier, region) => region.literal(self.lexer.source).to_string(),
+                (Token::String, region) => self.parse_string(region)?,
+                (token, region) => {
+                    return Err(Error::build(UNEXPECTED_TOKEN)
+                        .with_pointer(self.lexer.source, region)
+                        .with_help(format!(
+                            "expected an identifier or string as the key in object, \
+                            found `{token}`"
+                        )))
+                }
+            };
+            self.next_must(Token::Colon)?;
+            pairs.push((key, self.parse_base()?));
+
+            match self.next_any_must()? {
+                (Token::Comma, _) => continue,
+                (Token::RightBrace, end) => break end,
+                (token, region) => {
+                    return Err(Error::build(UNEXPECTED_TOKEN)
+                        .with_pointer(self.lexer.source, region)
+                        .with_help(format!(
+                            "expected `,` or `}}` after value in object, found `{token}`"
+                        )))
+                }
+            }
+        };
+        let region = begin.combine(end);
+
+        if pairs
+            .iter()
+            .all(|(_, value)| matches!(value, Base::Literal(_)))
+        {
+            let pairs = pairs.into_iter().map(|(key, value)| match value {
+                Base::Literal(literal) => (key, literal.value),
+                _ => unreachable!(),
+            });
+
+            return Ok(Base::Literal(Literal::new(
+                Value::Object(pairs.collect()),
+                region,
+            )));
+        }
+
+        Ok(Base::Object(Object { pairs, region }))
+    }
+
     /// Parse a [`Literal`] containing a [`Value::String`] from the literal value
     /// of the given [`Region`].
     ///
@@ -696,20 +1405,49 @@ impl<'source> Parser<'source> {
         Ok(Literal { value, region })
     }
 
-    /// Parse a [`Key`].
+    /// Parse a [`Key`] that follows a period.
+    ///
+    /// The `Key` may be an unquoted identifier such as `one.two`, or an integer
+    /// such as `items.0`.
     ///
     /// # Errors
     ///
-    /// Returns an [`Error`] if the next token is not a valid [`Identifier`].
-    fn parse_key(&mut self) -> Result<Identifier, Error> {
+    /// Returns an [`Error`] if the next token is not a valid [`Identifier`] or
+    /// integer.
+    fn parse_key(&mut self) -> Result<Key, Error> {
         match self.next_any_must()? {
-            (Token::Identifier, region) => Ok(Identifier { region }),
+            (Token::Identifier, region) => Ok(Key::Identifier(Identifier { region })),
+            (Token::Number, region) if self.lexer.source[region].parse::<usize>().is_ok() => {
+                let literal = self.parse_number_literal(&self.lexer.source[region], region)?;
+
+                Ok(Key::Index(Index {
+                    base: Box::new(Base::Literal(literal)),
+                    region,
+                }))
+            }
             (_, region) => Err(Error::build(UNEXPECTED_TOKEN)
                 .with_pointer(self.lexer.source, region)
-                .with_help("expected an unquoted identifier such as `one.two`")),
+                .with_help("expected an unquoted identifier such as `one.two` or an integer")),
         }
     }
 
+    /// Parse a [`Key`] within brackets, such as `[0]` or `[name]`, that begins
+    /// with the bracket at the given [`Region`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when an unexpected [`Token`] is found, or no `Token`
+    /// instances are left.
+    fn parse_subscript(&mut self, begin: Region) -> Result<Key, Error> {
+        let base = self.parse_base()?;
+        let (_, end) = self.next_must(Token::RightBracket)?;
+
+        Ok(Key::Index(Index {
+            base: Box::new(base),
+            region: begin.combine(end),
+        }))
+    }
+
     /// Parse a [`String`] from the literal value of the given [`Region`].
     ///
     /// # Errors
@@ -865,6 +1603,38 @@ impl<'source> Parser<'source> {
     }
 }
 
+/// Return true if the given [`Token`] ends a [`Condition`].
+fn is_condition_end(token: Token) -> bool {
+    matches!(
+        token,
+        Token::EndBlock
+            | Token::EndExpression
+            | Token::Keyword(Keyword::Else)
+            | Token::Pipe
+            | Token::Comma
+            | Token::RightParen
+            | Token::RightBracket
+            | Token::RightBrace
+    )
+}
+
+/// Return true if the innermost [`BlockState`] that is not an "if" block is the
+/// body of a "for" block.
+///
+/// The "else" of a "for" block is skipped, because it is rendered in place of
+/// the loop rather than within it.
+fn is_in_loop(states: &[BlockState]) -> bool {
+    for state in states.iter().rev() {
+        match state {
+            BlockState::If { .. } | BlockState::For { has_else: true, .. } => continue,
+            BlockState::For { .. } => return true,
+            BlockState::Block { .. } | BlockState::Macro { .. } => return false,
+        }
+    }
+
+    false
+}
+
 /// Return an [`Error`] describing an unexpected end of file.
 fn error_eof(source: &str) -> Error {
     let source_len = source.len();
@@ -878,16 +1648,18 @@ mod tests {
     use morel::{Finder, Kind};
 
     use crate::{
-        compile::{lex::token::Token, tree::Set},
+        compile::{lex::token::Token, tree::Set, Operator},
         region::Region,
         Builder,
     };
 
     use super::{
-        tree::{Expression, Tree},
+        tree::{Base, Binding, Condition, Expression, Tree},
         Parser,
     };
 
+    use serde_json::json;
+
     #[test]
     fn test_parser_lexer_integration() {
         let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
@@ -952,6 +1724,136 @@ mod tests {
         .is_err());
     }
 
+    #[test]
+    fn test_parse_arithmetic_precedence() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        let source = "(( a + b * (c - d) ))";
+        let base = get_parser_n(source, &finder, 1).parse_base().unwrap();
+
+        match base {
+            Base::Arithmetic(add) => {
+                assert_eq!(add.operator, Operator::Add);
+                assert_eq!(add.region.literal(source), "a + b * (c - d)");
+                match *add.right {
+                    Base::Arithmetic(multiply) => {
+                        assert_eq!(multiply.operator, Operator::Multiply);
+                        assert_eq!(multiply.right.get_region().literal(source), "(c - d)");
+                    }
+                    _ => panic!("expected `*` to bind before `+`"),
+                }
+            }
+            _ => panic!("expected arithmetic"),
+        }
+    }
+
+    #[test]
+    fn test_parse_fallback() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        let source = r#"(( a ?? b + 1 ?? "c" ))"#;
+        let base = get_parser_n(source, &finder, 1).parse_base().unwrap();
+
+        match base {
+            Base::Fallback(fallback) => {
+                assert_eq!(fallback.left.get_region().literal(source), "a");
+                assert_eq!(
+                    fallback.right.get_region().literal(source),
+                    r#"b + 1 ?? "c""#
+                );
+                assert!(matches!(*fallback.right, Base::Fallback(_)));
+            }
+            _ => panic!("expected fallback"),
+        }
+    }
+
+    #[test]
+    fn test_parse_arithmetic_group() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        let source = "(( (x ?? 1) * 2 ))";
+        let base = get_parser_n(source, &finder, 1).parse_base().unwrap();
+
+        match base {
+            Base::Arithmetic(multiply) => {
+                assert_eq!(multiply.operator, Operator::Multiply);
+                assert_eq!(multiply.left.get_region().literal(source), "(x ?? 1)");
+                assert!(matches!(*multiply.left, Base::Fallback(_)));
+            }
+            _ => panic!("expected arithmetic"),
+        }
+
+        let source = "(( (a if c else b) + 1 ))";
+        let base = get_parser_n(source, &finder, 1).parse_base().unwrap();
+
+        match base {
+            Base::Arithmetic(add) => {
+                assert_eq!(add.operator, Operator::Add);
+                assert_eq!(add.left.get_region().literal(source), "(a if c else b)");
+                assert!(matches!(*add.left, Base::Conditional(_)));
+            }
+            _ => panic!("expected arithmetic"),
+        }
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        let source = "(( 0..=count - 1 step 2 ))";
+        let base = get_parser_n(source, &finder, 1).parse_base().unwrap();
+
+        match base {
+            Base::Range(range) => {
+                assert!(range.inclusive);
+                assert_eq!(range.region.literal(source), "0..=count - 1 step 2");
+                assert_eq!(range.end.get_region().literal(source), "count - 1");
+                assert_eq!(range.step.unwrap().get_region().literal(source), "2");
+            }
+            _ => panic!("expected range"),
+        }
+    }
+
+    #[test]
+    fn test_parse_collection() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        let source = r#"(( [1, "two", { three: 3, "four": [4], },] ))"#;
+        let base = get_parser_n(source, &finder, 1).parse_base().unwrap();
+
+        match base {
+            Base::Literal(literal) => {
+                assert_eq!(
+                    literal.value,
+                    json!([1, "two", { "three": 3, "four": [4] }])
+                );
+                assert_eq!(literal.region.literal(source), &source[3..source.len() - 3]);
+            }
+            _ => panic!("expected literal"),
+        }
+
+        let source = "(( { name: user.name, tags: [1, tag] } ))";
+        let base = get_parser_n(source, &finder, 1).parse_base().unwrap();
+
+        match base {
+            Base::Object(object) => {
+                assert_eq!(object.pairs[0].0, "name");
+                assert!(matches!(object.pairs[0].1, Base::Variable(_)));
+                assert!(matches!(object.pairs[1].1, Base::Array(_)));
+            }
+            _ => panic!("expected object"),
+        }
+    }
+
+    #[test]
+    fn test_parse_collection_invalid() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+
+        for source in [
+            "(( [1 2] ))",
+            "(( [1, ))",
+            "(( { a 1 } ))",
+            "(( { 1: a } ))",
+        ] {
+            assert!(Parser::new(source, &finder).compile(None).is_err());
+        }
+    }
+
     #[test]
     fn test_peek_multiple() {
         let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
@@ -966,39 +1868,143 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_tree_valid() {
+    fn test_parse_condition_valid() {
         let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
         //                                                    --- negated
         let source = "(* if this >= that && these == those || not is_admin *)";
         //                  ------------    --------------    ------------
-        //                     Leaf 1           Leaf 2           Leaf 1
+        //                   Compare           Compare           Not
         //                  ------------------------------    ------------
-        //                              Branch 1                 Branch 2
+        //                              And                      |
         //                  ----------------------------------------------
-        //                                         Tree
-        let result = get_parser_n(source, &finder, 2).parse_tree().unwrap();
+        //                                         Or
+        let result = get_parser_n(source, &finder, 2).parse_condition().unwrap();
 
-        assert_eq!(result.branches.len(), 2);
-        assert_eq!(result.branches.first().unwrap().len(), 2);
-        assert_eq!(result.branches.last().unwrap().len(), 1);
+        match result {
+            Condition::Or(left, right) => {
+                assert!(matches!(*left, Condition::And(..)));
+                assert!(matches!(*right, Condition::Not(_)));
+            }
+            _ => panic!("expected `||` to bind last"),
+        }
+    }
+
+    #[test]
+    fn test_parse_condition_group() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        let source = "(* if not (a || b) && (c + 1) * 2 > d *)";
+        let result = get_parser_n(source, &finder, 2).parse_condition().unwrap();
+
+        match result {
+            Condition::And(left, right) => {
+                match *left {
+                    Condition::Not(group) => assert!(matches!(*group, Condition::Or(..))),
+                    _ => panic!("expected `not` to apply to the group"),
+                }
+                match *right {
+                    Condition::Compare(comparison) => {
+                        assert_eq!(comparison.left.get_region().literal(source), "(c + 1) * 2");
+                    }
+                    _ => panic!("expected comparison"),
+                }
+            }
+            _ => panic!("expected `&&` to bind last"),
+        }
+    }
+
+    #[test]
+    fn test_parse_condition_group_leading() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        let source = "(* if ((a || b) && c) *)";
+        let result = get_parser_n(source, &finder, 2).parse_condition().unwrap();
+
+        match result {
+            Condition::And(left, _) => assert!(matches!(*left, Condition::Or(..))),
+            _ => panic!("expected `&&` to bind last"),
+        }
     }
 
     #[test]
-    fn test_parse_tree_missing_base() {
+    fn test_parse_condition_membership() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        let source = "(* if role in roles && not name not in banned *)";
+        let result = get_parser_n(source, &finder, 2).parse_condition().unwrap();
+
+        match result {
+            Condition::And(left, right) => {
+                match *left {
+                    Condition::Compare(comparison) => assert_eq!(comparison.operator, Operator::In),
+                    _ => panic!("expected comparison"),
+                }
+                match *right {
+                    Condition::Not(not) => match *not {
+                        Condition::Compare(comparison) => {
+                            assert_eq!(comparison.operator, Operator::NotIn)
+                        }
+                        _ => panic!("expected comparison"),
+                    },
+                    _ => panic!("expected `not` to apply to the comparison"),
+                }
+            }
+            _ => panic!("expected `&&` to bind last"),
+        }
+        assert!(get_parser_n("(* if role not roles *)", &finder, 2)
+            .parse_condition()
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_condition_check() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        let source = "(* if count is divisible_by 3 && name is not empty *)";
+        let result = get_parser_n(source, &finder, 2).parse_condition().unwrap();
+
+        match result {
+            Condition::And(left, right) => {
+                match *left {
+                    Condition::Check(check) => {
+                        assert_eq!(check.name.region.literal(source), "divisible_by");
+                        assert_eq!(check.region.literal(source), "count is divisible_by 3");
+                        assert!(check.arguments.is_some());
+                    }
+                    _ => panic!("expected check"),
+                }
+                match *right {
+                    Condition::Not(not) => assert!(matches!(*not, Condition::Check(_))),
+                    _ => panic!("expected `is not` to negate the check"),
+                }
+            }
+            _ => panic!("expected `&&` to bind last"),
+        }
+        assert!(get_parser_n("(* if count is 3 *)", &finder, 2)
+            .parse_condition()
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_condition_missing_base() {
         let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
         let mut parser = get_parser_n("(* if this >= *)", &finder, 2);
         //                                          ^-- expected `Base` here
 
-        assert!(parser.parse_tree().is_err());
+        assert!(parser.parse_condition().is_err());
     }
 
     #[test]
-    fn test_parse_tree_bad_operator() {
+    fn test_parse_condition_bad_operator() {
         let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
         let mut parser = get_parser_n("(* if this = that *)", &finder, 2);
         //                                        ^-- did you mean `==`?
 
-        assert!(parser.parse_tree().is_err());
+        assert!(parser.parse_condition().is_err());
+    }
+
+    #[test]
+    fn test_parse_condition_unclosed_group() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        let mut parser = get_parser_n("(* if (a || b *)", &finder, 2);
+
+        assert!(parser.parse_condition().is_err());
     }
 
     #[test]
@@ -1035,6 +2041,179 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_raw() {
+        let source = "(* raw -*) (( name )) (* if *)(* end *)(*- end *)(( name ))";
+        let template = get_parser_n(
+            source,
+            &Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick),
+            0,
+        )
+        .compile(None)
+        .unwrap();
+
+        let mut iterator = template.get_scope().data.iter();
+        match iterator.next().unwrap() {
+            Tree::Raw(raw) => assert_eq!(raw.literal(source), "(( name )) (* if *)(* end *)"),
+            unexpected => panic!("expected raw text, found `{:?}`", unexpected),
+        }
+        assert!(matches!(iterator.next(), Some(Tree::Output(_))));
+    }
+
+    #[test]
+    fn test_parse_raw_nested() {
+        let source = "(* raw *)(* if a *)(* for b in c *)(( b ))(* end *)(* end *)\
+            (* raw *)(( d ))(* end *)(* end *)(( name ))";
+        let template = get_parser_n(
+            source,
+            &Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick),
+            0,
+        )
+        .compile(None)
+        .unwrap();
+
+        let mut iterator = template.get_scope().data.iter();
+        match iterator.next().unwrap() {
+            Tree::Raw(raw) => assert_eq!(
+                raw.literal(source),
+                "(* if a *)(* for b in c *)(( b ))(* end *)(* end *)(* raw *)(( d ))(* end *)"
+            ),
+            unexpected => panic!("expected raw text, found `{:?}`", unexpected),
+        }
+        assert!(matches!(iterator.next(), Some(Tree::Output(_))));
+        assert!(iterator.next().is_none());
+    }
+
+    #[test]
+    fn test_parse_raw_unclosed() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        let result = Parser::new("(* raw *)(( name ))(* end", &finder).compile(None);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_for_else_duplicate() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        let result =
+            Parser::new("(* for a in b *)(* else *)(* else *)(* end *)", &finder).compile(None);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_break_outside_loop() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+
+        for source in [
+            "(* break *)",
+            "(* if a *)(* continue *)(* end *)",
+            "(* for a in b *)(* else *)(* break *)(* end *)",
+            "(* for a in b *)(* block c *)(* break *)(* end *)(* end *)",
+        ] {
+            assert!(Parser::new(source, &finder).compile(None).is_err());
+        }
+        assert!(Parser::new(
+            "(* for a in b *)(* if a *)(* break *)(* else *)(* continue *)(* end *)(* end *)",
+            &finder
+        )
+        .compile(None)
+        .is_ok());
+    }
+
+    #[test]
+    fn test_parse_macro() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        let source = "(* macro button(label, kind=\"primary\") *)(( label ))(* end *)\
+            (( button(\"Save\", kind: \"link\") ))";
+        let template = Parser::new(source, &finder).compile(None).unwrap();
+
+        let definition = template.get_macro("button").unwrap();
+        assert_eq!(definition.parameters.len(), 2);
+        assert!(definition.parameters[0].default.is_none());
+        assert!(definition.parameters[1].default.is_some());
+        assert_eq!(definition.scope.data.len(), 1);
+
+        assert_eq!(template.get_scope().data.len(), 1);
+        match &template.get_scope().data[0] {
+            Tree::Output(output) => match &output.expression {
+                Expression::Base(Base::Invoke(invoke)) => {
+                    assert_eq!(invoke.arguments.len(), 2);
+                    assert_eq!(
+                        invoke.region.literal(source),
+                        "button(\"Save\", kind: \"link\")"
+                    );
+                }
+                _ => panic!("expected invoke"),
+            },
+            _ => panic!("expected output"),
+        }
+    }
+
+    #[test]
+    fn test_parse_macro_invalid() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+
+        for source in [
+            "(* if a *)(* macro b() *)(* end *)(* end *)",
+            "(* macro b(c, c) *)(* end *)",
+            "(* macro b(c=1, d) *)(* end *)",
+            "(* macro b() *)(* end *)(* macro b() *)(* end *)",
+            "(* macro b *)(* end *)",
+            "(* macro b() *)",
+            "(( b(1 2) ))",
+        ] {
+            assert!(Parser::new(source, &finder).compile(None).is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_import() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+        let source = "(* import \"forms.html\" as forms *)\
+            (* from \"forms.html\" import input, select *)(( forms.input() ))";
+        let template = Parser::new(source, &finder).compile(None).unwrap();
+
+        let imports = template.get_imports();
+        assert_eq!(imports.len(), 2);
+        assert_eq!(
+            imports[0].region.literal(source),
+            "(* import \"forms.html\" as forms *)"
+        );
+        match &imports[0].binding {
+            Binding::Namespace(namespace) => assert_eq!(namespace.region.literal(source), "forms"),
+            _ => panic!("expected namespace"),
+        }
+        match &imports[1].binding {
+            Binding::Macros(macros) => assert_eq!(
+                macros
+                    .iter()
+                    .map(|identifier| identifier.region.literal(source))
+                    .collect::<Vec<_>>(),
+                vec!["input", "select"]
+            ),
+            _ => panic!("expected macros"),
+        }
+        assert_eq!(template.get_scope().data.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_import_invalid() {
+        let finder = Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick);
+
+        for source in [
+            "(* if a *)(* import \"b\" as b *)(* end *)",
+            "(* import \"b\" *)",
+            "(* import \"b\" to b *)",
+            "(* from \"b\" import *)",
+            "(* from \"b\" import c d *)",
+            "(* import \"b\" as c *)(* from \"d\" import c *)",
+            "(* from \"b\" import c, c *)",
+        ] {
+            assert!(Parser::new(source, &finder).compile(None).is_err());
+        }
+    }
+
     #[test]
     fn test_parse_block() {
         //                     ---- name
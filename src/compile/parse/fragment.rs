@@ -1,15 +1,15 @@
 use std::fmt::Display;
 
-use crate::compile::tree::{IfTree, Set};
+use crate::compile::tree::{Condition, Set};
 
-use super::tree::{Base, Expression, Identifier, Mount};
+use super::tree::{Base, Binding, Expression, Identifier, Mount, Parameter};
 
 /// Represents a fragment of a larger expression.
 pub enum Fragment {
-    /// The first part of an "if" block, containing a [`IfTree`].
-    If(IfTree),
-    /// An additional [`IfTree`] provided to a parent "if" block.
-    ElseIf(IfTree),
+    /// The first part of an "if" block, containing a [`Condition`].
+    If(Condition),
+    /// An additional [`Condition`] provided to a parent "if" block.
+    ElseIf(Condition),
     /// A default value for a parent "if" block.
     Else,
     /// The first part of an "if" block, containing a set of faux
@@ -27,6 +27,19 @@ pub enum Fragment {
     /// A "block" expression, defines an area that can be overridden by
     /// another extending template.
     Block(Base),
+    /// The first part of a "macro" block, containing the name and parameters.
+    Macro(Identifier, Vec<Parameter>),
+    /// An "import" or "from" expression, makes the macros of another
+    /// template available.
+    ///
+    /// Must be found at the top level of a `Template`.
+    Import(Base, Binding),
+    /// A "raw" expression, the text up to the following "end" is not parsed.
+    Raw,
+    /// A "break" expression, stops the enclosing loop.
+    Break,
+    /// A "continue" expression, skips to the next iteration of the enclosing loop.
+    Continue,
     /// Closes a block.
     End,
 }
@@ -42,6 +55,11 @@ impl Display for Fragment {
             Fragment::Include(_, _) => write!(f, "include"),
             Fragment::Extends(_) => write!(f, "extends"),
             Fragment::Block(_) => write!(f, "block"),
+            Fragment::Macro(_, _) => write!(f, "macro"),
+            Fragment::Import(_, _) => write!(f, "import"),
+            Fragment::Raw => write!(f, "raw"),
+            Fragment::Break => write!(f, "break"),
+            Fragment::Continue => write!(f, "continue"),
             Fragment::End => write!(f, "end"),
         }
     }
@@ -22,6 +22,10 @@ pub enum Tree {
     Let(Let),
     /// Template block.
     Block(Block),
+    /// Stop the enclosing loop.
+    Break,
+    /// Skip to the next iteration of the enclosing loop.
+    Continue,
 }
 
 /// Represents a section of text that may be overridden by another [`Block`].
@@ -68,91 +72,78 @@ impl Expression {
     }
 }
 
-/// Storage for a stack of [`IfBranch`] instances.
+/// A boolean expression that determines which branch of an [`If`] or
+/// [`Conditional`] is used.
 ///
-/// For example, this example represents one [`IfTree`] made up of two
-/// [`IfBranch`] instances, which are separated at the "||" characters:
+/// From highest to lowest precedence, a `Condition` is made of comparisons,
+/// `not`, `&&` and `||`. Parentheses may be used to group a `Condition`:
 ///
-/// this > that && those == these || they > them
-#[derive(Debug, Clone)]
-pub struct IfTree {
-    pub branches: Vec<IfBranch>,
+/// this > that && (these == those || not is_admin)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// True when both [`Condition`] instances are true, such as `a && b`.
+    And(Box<Condition>, Box<Condition>),
+    /// True when either [`Condition`] is true, such as `a || b`.
+    Or(Box<Condition>, Box<Condition>),
+    /// True when the [`Condition`] is false, such as `not a`.
+    Not(Box<Condition>),
+    /// Compares two [`Base`] instances with an [`Operator`], such as `a > b`.
+    Compare(Comparison),
+    /// Applies a [`Test`][`crate::test::Test`] to a [`Base`], such as `a is even`.
+    Check(Check),
+    /// True when the [`Base`] is truthy, such as `a`.
+    Truthy(Base),
 }
 
-impl IfTree {
-    /// Create a new [`IfTree`] with one initialized [`IfBranch`].
-    pub fn new() -> Self {
-        Self {
-            branches: vec![IfBranch::new()],
-        }
-    }
-
-    /// Split up the [`IfTree`] by adding a new [`IfBranch`].
-    ///
-    /// Any additional [`IfLeaf`] instances will be added to this new `IfBranch`.
-    pub fn split_branch(&mut self) {
-        self.branches.push(IfBranch::new());
-    }
-
-    /// Split up the last [`IfBranch`] in the [`IfTree`] by starting
-    /// a new [`IfLeaf`].
-    pub fn split_leaf(&mut self, leaf: IfLeaf) {
-        self.last_mut_must().push(leaf);
-    }
-
-    /// Return a mutable reference to the last [`IfBranch`] in the
-    /// [`IfTree`].
-    ///
-    /// # Panics
-    ///
-    /// Will panic if no `IfBranch` instances are in the `IfTree`,
-    /// which should never happen if created with .new().
-    pub fn last_mut_must(&mut self) -> &mut IfBranch {
-        self.branches
-            .last_mut()
-            .expect("tree should always have >1 branch")
-    }
-
-    /// Return a mutable reference to the last [`IfLeaf`] in the last
-    /// [`IfBranch`] in the [`IfTree`].
+impl Condition {
+    /// Get a [`Region`] from the underlying [`Condition`] kind.
     ///
-    /// # Panics
-    ///
-    /// Will panic with the given reason if the `IfBranch` is empty.
-    pub fn last_leaf_mut_must(&mut self, reason: &str) -> &mut IfLeaf {
-        self.last_mut_must().last_mut().expect(reason)
+    /// The `Region` of [`Condition::Not`] does not include the `not` keyword.
+    pub fn get_region(&self) -> Region {
+        match self {
+            Condition::And(left, right) | Condition::Or(left, right) => {
+                left.get_region().combine(right.get_region())
+            }
+            Condition::Not(condition) => condition.get_region(),
+            Condition::Compare(comparison) => comparison.get_region(),
+            Condition::Check(check) => check.region,
+            Condition::Truthy(base) => base.get_region(),
+        }
     }
 }
 
-/// A single set of [`IfLeaf`] instances.
-pub type IfBranch = Vec<IfLeaf>;
-
 /// Represents a comparison between two [`Base`] instances with some
 /// [`Operator`].
-#[derive(Debug, Clone)]
-pub struct IfLeaf {
-    /// True if the [`IfLeaf`] is negated.
-    pub negate: bool,
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
     /// The [`Base`] to the left of the [`Operator`].
     pub left: Base,
     /// The [`Operator`] used to compare left and right.
-    pub operator: Option<Operator>,
+    pub operator: Operator,
     /// The [`Base`] to the right of the [`Operator`].
-    pub right: Option<Base>,
+    pub right: Base,
 }
 
-impl IfLeaf {
-    /// Create a new [`IfLeaf`] from the given [`Base`].
-    pub fn new(base: Base, negate: bool) -> Self {
-        Self {
-            negate,
-            left: base,
-            operator: None,
-            right: None,
-        }
+impl Comparison {
+    /// Get a [`Region`] from the left to the right [`Base`].
+    pub fn get_region(&self) -> Region {
+        self.left.get_region().combine(self.right.get_region())
     }
 }
 
+/// Command to execute a [`Test`][`crate::test::Test`] on a [`Base`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    /// The [`Base`] that is tested.
+    pub base: Base,
+    /// The name of the [`Test`][`crate::test::Test`].
+    pub name: Identifier,
+    /// [`Arguments`] passed to the [`Test`][`crate::test::Test`].
+    pub arguments: Option<Arguments>,
+    /// The location of the [`Check`], from the `Base` to the last `Argument`.
+    pub region: Region,
+}
+
 /// Represents a call to render some kind of Expression.
 #[derive(Debug, Clone)]
 pub struct Output {
@@ -181,12 +172,56 @@ impl From<(Expression, Region)> for Output {
 ///
 /// A [`Variable`] is an Identifier such as "person.name" which represents
 /// a path to a [`Value`] within the `Store`.
+///
+/// ## Arithmetic
+///
+/// An [`Arithmetic`] combines two other `Base` instances with an [`Operator`],
+/// such as "price * quantity".
+///
+/// ## Range
+///
+/// A [`Range`] is a sequence of integers between two other `Base` instances,
+/// such as "0..count".
+///
+/// ## Array and Object
+///
+/// An [`Array`] or [`Object`] is a collection of other `Base` instances, such as
+/// `[1, name]` or `{ title: page.title }`, which are evaluated at render time.
+///
+/// ## Fallback
+///
+/// A [`Fallback`] evaluates to the `Base` on its right when the `Base` on its
+/// left is missing or null, such as "user.nickname ?? user.name".
+///
+/// ## Conditional
+///
+/// A [`Conditional`] chooses between two `Base` instances with a [`Condition`],
+/// such as `"active" if selected else ""`.
+///
+/// ## Invoke
+///
+/// An [`Invoke`] renders a [`Macro`] with some arguments, such as
+/// `button("Save")`, and evaluates to the output.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Base {
     /// A value located in the Store.
     Variable(Variable),
     /// A literal value located directly in the template source.
     Literal(Literal),
+    /// An arithmetic operation on two other values.
+    Arithmetic(Arithmetic),
+    /// A sequence of integers between two other values.
+    Range(Range),
+    /// An array of other values.
+    Array(Array),
+    /// An object of other values.
+    Object(Object),
+    /// A value to use in place of a missing or null value.
+    Fallback(Fallback),
+    /// A choice between two values.
+    Conditional(Conditional),
+    /// The output of a [`Macro`].
+    Invoke(Invoke),
 }
 
 impl Base {
@@ -195,35 +230,224 @@ impl Base {
         match self {
             Base::Variable(variable) => variable.get_region(),
             Base::Literal(literal) => literal.region,
+            Base::Arithmetic(arithmetic) => arithmetic.region,
+            Base::Range(range) => range.region,
+            Base::Array(array) => array.region,
+            Base::Object(object) => object.region,
+            Base::Fallback(fallback) => fallback.region,
+            Base::Conditional(conditional) => conditional.region,
+            Base::Invoke(invoke) => invoke.region,
         }
     }
 }
 
-/// Set of [`Identifier`] instances that can be used to locate data
+/// An arithmetic [`Operator`] applied to two [`Base`] instances.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arithmetic {
+    /// The [`Base`] to the left of the [`Operator`].
+    pub left: Box<Base>,
+    /// The [`Operator`] applied to left and right.
+    pub operator: Operator,
+    /// The [`Base`] to the right of the [`Operator`].
+    pub right: Box<Base>,
+    /// The location of the [`Arithmetic`].
+    pub region: Region,
+}
+
+impl Arithmetic {
+    /// Create a new [`Arithmetic`] from the given [`Base`] instances and [`Operator`].
+    pub fn new(left: Base, operator: Operator, right: Base) -> Self {
+        let region = left.get_region().combine(right.get_region());
+
+        Self {
+            left: Box::new(left),
+            operator,
+            right: Box::new(right),
+            region,
+        }
+    }
+}
+
+/// A sequence of integers from a start [`Base`] to an end `Base`, such as
+/// `0..10`, `0..=10` or `0..10 step 2`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    /// The [`Base`] that the [`Range`] starts at, inclusive.
+    pub start: Box<Base>,
+    /// The [`Base`] that the [`Range`] ends at.
+    pub end: Box<Base>,
+    /// The [`Base`] added to each integer to produce the next, or [`None`]
+    /// to count up by one.
+    pub step: Option<Box<Base>>,
+    /// True if the end is included in the [`Range`].
+    pub inclusive: bool,
+    /// The location of the [`Range`].
+    pub region: Region,
+}
+
+/// An inline choice between two [`Base`] instances, such as
+/// `"active" if selected else ""`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conditional {
+    /// The [`Base`] to use when the [`Condition`] is true.
+    pub then_base: Box<Base>,
+    /// Determines which [`Base`] to use.
+    pub condition: Box<Condition>,
+    /// The [`Base`] to use when the [`Condition`] is false, or [`None`]
+    /// to use null.
+    pub else_base: Option<Box<Base>>,
+    /// The location of the [`Conditional`].
+    pub region: Region,
+}
+
+/// A [`Base`] that is used in place of another `Base` when it is missing
+/// or null.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fallback {
+    /// The [`Base`] to use when it exists and is not null.
+    pub left: Box<Base>,
+    /// The [`Base`] to use otherwise.
+    pub right: Box<Base>,
+    /// The location of the [`Fallback`].
+    pub region: Region,
+}
+
+/// An array literal, such as `[1, name]`, that contains at least one
+/// [`Base`] which is not a [`Literal`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Array {
+    /// The [`Base`] instances within the [`Array`].
+    pub values: Vec<Base>,
+    /// The location of the [`Array`], including the brackets.
+    pub region: Region,
+}
+
+/// An object literal, such as `{ title: page.title }`, that contains at least
+/// one [`Base`] which is not a [`Literal`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    /// The keys and [`Base`] instances within the [`Object`].
+    pub pairs: Vec<(String, Base)>,
+    /// The location of the [`Object`], including the braces.
+    pub region: Region,
+}
+
+/// A call to a [`Macro`], such as `button("Save", kind: "secondary")`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invoke {
+    /// The name of the [`Macro`].
+    pub name: Variable,
+    /// The [`Argument`] instances passed to the [`Macro`], which are matched to
+    /// its [`Parameter`] instances by position or by name.
+    pub arguments: Vec<Argument>,
+    /// The location of the [`Invoke`], including the parentheses.
+    pub region: Region,
+}
+
+/// A reusable [`Scope`] with parameters, defined at the top level of a
+/// [`Template`][`crate::Template`].
+#[derive(Debug, Clone)]
+pub struct Macro {
+    /// The name of the [`Macro`].
+    pub name: Identifier,
+    /// The [`Parameter`] instances of the [`Macro`], in order.
+    pub parameters: Vec<Parameter>,
+    /// The data inside of the [`Macro`].
+    pub scope: Scope,
+}
+
+/// A statement that makes the [`Macro`] instances of another
+/// [`Template`][`crate::Template`] available, such as
+/// `import "forms.html" as forms` or `from "forms.html" import input`.
+#[derive(Debug, Clone)]
+pub struct Import {
+    /// The name of the [`Template`][`crate::Template`] to import from.
+    pub name: Base,
+    /// The names that the imported [`Macro`] instances are available under.
+    pub binding: Binding,
+    /// The location of the [`Import`].
+    pub region: Region,
+}
+
+/// The names that an [`Import`] makes available.
+#[derive(Debug, Clone)]
+pub enum Binding {
+    /// Every [`Macro`] is available within a namespace, such as `forms` in
+    /// `import "forms.html" as forms`, and is invoked like `forms.input()`.
+    Namespace(Identifier),
+    /// Each listed [`Macro`] is available by name, such as `input` in
+    /// `from "forms.html" import input`.
+    Macros(Vec<Identifier>),
+}
+
+/// A parameter of a [`Macro`], such as `kind="primary"`.
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    /// The name of the [`Parameter`].
+    pub name: Identifier,
+    /// The [`Base`] used when no [`Argument`] is given for the [`Parameter`].
+    pub default: Option<Base>,
+}
+
+/// Set of [`Key`] instances that can be used to locate data
 /// within the [`Store`][`crate::Store`].
 #[derive(Debug, Clone, PartialEq)]
 pub struct Variable {
-    /// A set of [`Identifier`] instances that form  a path through the
+    /// A set of [`Key`] instances that form  a path through the
     /// [`Store`][`crate::Store`] to some [`Value`].
-    pub path: Vec<Identifier>,
+    ///
+    /// The first `Key` is always a [`Key::Identifier`].
+    pub path: Vec<Key>,
 }
 
 impl Variable {
     /// Create a new [`Variable`] from the given keys.
-    pub fn new(path: Vec<Identifier>) -> Self {
+    pub fn new(path: Vec<Key>) -> Self {
         Self { path }
     }
 
-    /// Get a [`Region`] from the first to last [`Identifier`] instance.
+    /// Get a [`Region`] from the first to last [`Key`] instance.
     pub fn get_region(&self) -> Region {
         self.path
             .first()
             .unwrap()
-            .region
-            .combine(self.path.last().unwrap().region)
+            .get_region()
+            .combine(self.path.last().unwrap().get_region())
     }
 }
 
+/// A single step in the path of a [`Variable`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Key {
+    /// An unquoted name, such as `name` in `user.name`.
+    Identifier(Identifier),
+    /// A [`Base`] that evaluates to the key, such as `[0]` in `items[0]`
+    /// or `0` in `items.0`.
+    Index(Index),
+}
+
+impl Key {
+    /// Get a [`Region`] from the underlying [`Key`] kind.
+    pub fn get_region(&self) -> Region {
+        match self {
+            Key::Identifier(identifier) => identifier.region,
+            Key::Index(index) => index.region,
+        }
+    }
+}
+
+/// A [`Base`] used to look up a [`Value`] within an array or object.
+///
+/// Integers index arrays, and may be negative to count from the end of the
+/// array, while strings index objects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Index {
+    /// The [`Base`] that evaluates to the key.
+    pub base: Box<Base>,
+    /// The location of the [`Index`], including any brackets.
+    pub region: Region,
+}
+
 /// Area that contains an identifying value.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Identifier {
@@ -264,7 +488,7 @@ pub struct Call {
 
 /// Set of arguments that can be provided to a
 /// [`Filter`][`crate::filter::Filter`].
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Arguments {
     /// A set of [`Argument`] instances, representing the arguments
     /// passed to a [`Filter`][`crate::filter::Filter`].
@@ -274,7 +498,7 @@ pub struct Arguments {
 }
 
 /// A single argument.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Argument {
     /// The name of the [`Argument`], may be None
     pub name: Option<Region>,
@@ -327,13 +551,11 @@ pub struct Include {
 /// Conditional rendering block.
 #[derive(Debug, Clone)]
 pub struct If {
-    /// Contains the data needed to determine which branch to render.
-    pub tree: IfTree,
-    /// The [`Scope`] to render if the [`IfTree`] contains a truthy
-    /// [`IfBranch`].
+    /// Determines which branch to render.
+    pub condition: Condition,
+    /// The [`Scope`] to render if the [`Condition`] is true.
     pub then_branch: Scope,
-    /// The [`Scope`] to render if the [`IfTree`] does not contain
-    /// a truthy [`IfBranch`].
+    /// The [`Scope`] to render if the [`Condition`] is false.
     pub else_branch: Option<Scope>,
     /// The location of the [`If`].
     pub region: Region,
@@ -348,6 +570,8 @@ pub struct For {
     pub base: Base,
     /// The [`Scope`] that is rendered with each iteration.
     pub scope: Scope,
+    /// The [`Scope`] to render if the [`Base`] is empty.
+    pub else_scope: Option<Scope>,
     /// The location of the [`Iterable`].
     pub region: Region,
 }
@@ -24,6 +24,16 @@ pub enum Marker {
     /// Same as EndBlock, but causes the leading whitespace of the
     /// following raw text to be removed.
     EndBlockTrim = 7,
+    /// Beginning of a Comment, which contains text that is not rendered.
+    BeginComment = 8,
+    /// End of a Comment.
+    EndComment = 9,
+    /// Same as BeginComment, but causes the trailing whitespace of the
+    /// preceding raw text to be removed.
+    BeginCommentTrim = 10,
+    /// Same as EndComment, but causes the leading whitespace of the
+    /// following raw text to be removed.
+    EndCommentTrim = 11,
 }
 
 impl From<usize> for Marker {
@@ -37,6 +47,10 @@ impl From<usize> for Marker {
             5 => Self::EndBlock,
             6 => Self::BeginBlockTrim,
             7 => Self::EndBlockTrim,
+            8 => Self::BeginComment,
+            9 => Self::EndComment,
+            10 => Self::BeginCommentTrim,
+            11 => Self::EndCommentTrim,
             _ => unreachable!(),
         }
     }
@@ -61,8 +75,13 @@ impl From<Marker> for usize {
 ///     .to_syntax();
 /// ```
 pub struct Builder<'marker> {
+    /// The beginning and end expression markers.
     expression: (&'marker str, &'marker str),
+    /// The beginning and end block markers.
     block: (&'marker str, &'marker str),
+    /// The beginning and end comment markers, if comments are enabled.
+    comment: Option<(&'marker str, &'marker str)>,
+    /// The character that causes a marker to trim whitespace.
     whitespace: &'marker char,
 }
 
@@ -79,6 +98,9 @@ impl<'marker> Builder<'marker> {
     ///     Block:  (*- if ... -*)
     /// ```
     ///
+    /// Comments have no default markers, so text like `(#42)` is not changed.
+    /// Enable them with [`with_comment`][`Builder::with_comment`].
+    ///
     /// To proceed with these defaults, you may immediately call `to_syntax` to receive the
     /// [`Syntax`] instance.
     #[inline]
@@ -86,6 +108,7 @@ impl<'marker> Builder<'marker> {
         Self {
             expression: ("((", "))"),
             block: ("(*", "*)"),
+            comment: None,
             whitespace: &'-',
         }
     }
@@ -156,6 +179,40 @@ impl<'marker> Builder<'marker> {
         self
     }
 
+    /// Set the comment markers, which enables comments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ban::Builder;
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.set_comment("{#", "#}");
+    /// ```
+    #[inline]
+    pub fn set_comment(&mut self, begin: &'marker str, end: &'marker str) {
+        self.comment = Some((begin, end));
+    }
+
+    /// Set the comment markers, which enables comments.
+    ///
+    /// Returns the [`Builder`], so additional methods may be chained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ban::Builder;
+    ///
+    /// Builder::new()
+    ///     .with_comment("{#", "#}");
+    /// ```
+    #[inline]
+    pub fn with_comment(mut self, begin: &'marker str, end: &'marker str) -> Self {
+        self.set_comment(begin, end);
+
+        self
+    }
+
     /// Set the whitespace trim character.
     ///
     /// # Examples
@@ -200,6 +257,7 @@ impl<'marker> Builder<'marker> {
     /// let syntax = Builder::new()
     ///     .with_expression("{{", "}}")
     ///     .with_block("{*", "*}")
+    ///     .with_comment("{#", "#}")
     ///     .with_whitespace(&'!')
     ///     .to_syntax();
     /// ```
@@ -229,6 +287,18 @@ impl<'marker> Builder<'marker> {
             Marker::EndBlockTrim.into(),
             format!("{whitespace}{right_block}"),
         ));
+        if let Some((left_comment, right_comment)) = self.comment {
+            markers.push((Marker::BeginComment.into(), left_comment.into()));
+            markers.push((Marker::EndComment.into(), right_comment.into()));
+            markers.push((
+                Marker::BeginCommentTrim.into(),
+                format!("{left_comment}{whitespace}"),
+            ));
+            markers.push((
+                Marker::EndCommentTrim.into(),
+                format!("{whitespace}{right_comment}"),
+            ));
+        }
 
         Syntax::new(markers)
     }
@@ -1,4 +1,9 @@
-use super::{tree::Extends, Scope};
+use std::collections::HashMap;
+
+use super::{
+    tree::{Extends, Import, Macro},
+    Scope,
+};
 
 /// A compiled [`Template`] that can be rendered against a [`Store`][`crate::Store`].
 #[derive(Debug, Clone)]
@@ -12,6 +17,10 @@ pub struct Template {
     /// If the [`Template`] is extended, contains information about the other
     /// `Template`.
     extends: Option<Extends>,
+    /// The [`Macro`] instances defined in the [`Template`], by name.
+    macros: HashMap<String, Macro>,
+    /// The [`Import`] instances of the [`Template`], in order.
+    imports: Vec<Import>,
 }
 
 impl Template {
@@ -21,12 +30,16 @@ impl Template {
         scope: Scope,
         source: String,
         extends: Option<Extends>,
+        macros: HashMap<String, Macro>,
+        imports: Vec<Import>,
     ) -> Self {
         Self {
             name,
             scope,
             source,
             extends,
+            macros,
+            imports,
         }
     }
 
@@ -53,4 +66,16 @@ impl Template {
     pub(crate) fn get_scope(&self) -> &Scope {
         &self.scope
     }
+
+    /// Return a reference to the named [`Macro`] of the [`Template`].
+    #[inline]
+    pub(crate) fn get_macro(&self, name: &str) -> Option<&Macro> {
+        self.macros.get(name)
+    }
+
+    /// Return a reference to the [`Import`] instances of the [`Template`].
+    #[inline]
+    pub(crate) fn get_imports(&self) -> &[Import] {
+        &self.imports
+    }
 }
@@ -1,23 +1,61 @@
-use std::collections::HashMap;
+pub mod load;
+
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, BufWriter},
+    path::Path,
+    sync::{Arc, PoisonError, RwLock},
+    time::SystemTime,
+};
 
 use crate::{
     compile::{Parser, Template},
     log::Error,
-    render::{filter::Filter, pipe::Pipe, Renderer},
-    Builder, Store,
+    render::{
+        error_missing_template, error_write,
+        filter::{identity, Filter, Safe},
+        pipe::{IoWriter, Pipe},
+        test::Test,
+        Renderer,
+    },
+    Builder, Escape, Store, Undefined,
 };
 
+use self::load::Loader;
+
 use morel::{Finder, Kind, Syntax};
 
 pub const INVALID_FILTER: &str = "invalid filter";
+pub const INVALID_TEST: &str = "invalid test";
+
+/// Names of the tests that are built into the renderer, which cannot be added
+/// to an [`Engine`].
+pub const RESERVED_TESTS: [&str; 2] = ["defined", "undefined"];
 
 /// Facilitates compiling and rendering templates, and provides storage
-/// for filters.
+/// for filters and tests.
 pub struct Engine {
     /// [`Filter`] instances assigned to this [`Engine`].
     filters: HashMap<String, Box<dyn Filter>>,
+    /// [`Test`] instances assigned to this [`Engine`].
+    tests: HashMap<String, Box<dyn Test>>,
     /// [`Template`] instances assigned to this [`Engine`].
-    templates: HashMap<String, Template>,
+    templates: HashMap<String, Arc<Template>>,
+    /// [`Loader`] consulted when a [`Template`] is not found in `templates`.
+    loader: Option<Box<dyn Loader>>,
+    /// [`Template`] instances compiled from the source text returned by the `loader`.
+    cache: RwLock<HashMap<String, Cached>>,
+    /// When true, a cached [`Template`] is compiled again if the `loader` reports
+    /// that its source was modified.
+    reload: bool,
+    /// Determines when the output of expressions is escaped for HTML.
+    escape: Escape,
+    /// Determines what happens when a variable does not exist.
+    undefined: Undefined,
+    /// When true, strings that contain a number are compared with numbers as
+    /// numbers.
+    coerce: bool,
     /// [`Finder`] used to compile [`Template`] instances.
     finder: Finder,
 }
@@ -25,6 +63,10 @@ pub struct Engine {
 impl Engine {
     /// Create a new [`Engine`] with the given `Syntax`.
     ///
+    /// The `Engine` has the built-in `safe` [`Filter`], which marks a value as safe
+    /// to render without escaping, and every [`Test`] in the standard
+    /// [`library`][`crate::test::library`].
+    ///
     /// # Examples
     ///
     /// ```
@@ -39,11 +81,24 @@ impl Engine {
     /// ```
     #[inline]
     pub fn new(syntax: Syntax) -> Self {
-        Self {
-            filters: HashMap::new(),
+        let mut filters: HashMap<String, Box<dyn Filter>> = HashMap::new();
+        filters.insert("safe".to_string(), Box::new(Safe(identity)));
+
+        let mut engine = Self {
+            filters,
+            tests: HashMap::new(),
             templates: HashMap::new(),
+            loader: None,
+            cache: RwLock::new(HashMap::new()),
+            reload: false,
+            escape: Escape::default(),
+            undefined: Undefined::default(),
+            coerce: false,
             finder: Finder::new(syntax, Kind::AhoCorasick),
-        }
+        };
+        engine.add_std_tests();
+
+        engine
     }
 
     /// Compile a new [`Template`].
@@ -108,12 +163,111 @@ impl Engine {
     /// ```
     #[inline]
     pub fn render(&self, template: &Template, store: &Store) -> Result<String, Error> {
+        let reloaded = self.reload_template(template)?;
+        let template = reloaded.as_deref().unwrap_or(template);
+
         let mut buffer = get_buffer(template);
         Renderer::new(self, template, store).render(&mut Pipe::new(&mut buffer))?;
 
         Ok(buffer)
     }
 
+    /// Render a [`Template`] with the given [`Store`], writing the output to the given
+    /// [`fmt::Write`] as it is produced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if rendering fails, or the writer returns an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ban::{Store, Engine};
+    ///
+    /// let engine = Engine::default();
+    /// let template = engine.compile_must("hello, (( name ))!");
+    ///
+    /// let mut buffer = String::new();
+    /// engine
+    ///     .render_to(&template, &Store::new().with_must("name", "taylor"), &mut buffer)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(buffer, "hello, taylor!")
+    /// ```
+    pub fn render_to<W>(
+        &self,
+        template: &Template,
+        store: &Store,
+        writer: &mut W,
+    ) -> Result<(), Error>
+    where
+        W: fmt::Write,
+    {
+        let reloaded = self.reload_template(template)?;
+        let template = reloaded.as_deref().unwrap_or(template);
+
+        Renderer::new(self, template, store).render(&mut Pipe::new(writer))
+    }
+
+    /// Render a [`Template`] with the given [`Store`], writing the output to the given
+    /// [`io::Write`] as it is produced.
+    ///
+    /// This allows output to be sent straight to a file or socket without holding
+    /// all of it in memory. Output is buffered, so the writer receives it in large
+    /// chunks rather than one write per expression, and the writer is flushed once
+    /// rendering is complete.
+    ///
+    /// When rendering fails, output that is still buffered is discarded rather than
+    /// written, but output that the writer has already received is left as it is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if rendering fails, or the writer returns an [`io::Error`],
+    /// in which case the `io::Error` is described by the `Error`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ban::{Store, Engine};
+    ///
+    /// let engine = Engine::default();
+    /// let template = engine.compile_must("hello, (( name ))!");
+    ///
+    /// let mut buffer: Vec<u8> = vec![];
+    /// engine
+    ///     .render_to_io(&template, &Store::new().with_must("name", "taylor"), &mut buffer)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(buffer, b"hello, taylor!")
+    /// ```
+    pub fn render_to_io<W>(
+        &self,
+        template: &Template,
+        store: &Store,
+        writer: &mut W,
+    ) -> Result<(), Error>
+    where
+        W: io::Write,
+    {
+        let mut buffered = BufWriter::new(writer);
+        let mut writer = IoWriter::new(&mut buffered);
+        let result = self
+            .render_to(template, store, &mut writer)
+            .map_err(|error| match writer.take_error() {
+                Some(io) => error.with_help(format!("failed to write result of render: {io}")),
+                None => error,
+            });
+        if let Err(error) = result {
+            // Dropping the writer would flush the output of a failed render.
+            let _ = buffered.into_parts();
+            return Err(error);
+        }
+
+        writer.flush().map_err(|io| {
+            error_write().with_help(format!("failed to flush result of render: {io}"))
+        })
+    }
+
     /// Store an existing [`Template`] in the [`Engine`].
     ///
     /// # Examples
@@ -139,7 +293,7 @@ impl Engine {
     where
         T: Into<String>,
     {
-        self.templates.insert(name.into(), template);
+        self.templates.insert(name.into(), Arc::new(template));
     }
 
     /// Store an existing [`Template`] in the [`Engine`].
@@ -206,7 +360,7 @@ impl Engine {
             .compile(Some(name.to_owned()))
             .map_err(|error| error.with_name(name))?;
 
-        self.templates.insert(name.to_owned(), template);
+        self.templates.insert(name.to_owned(), Arc::new(template));
 
         Ok(())
     }
@@ -234,11 +388,81 @@ impl Engine {
             .compile(Some(name.to_owned()))
             .map_err(|error| error.with_name(name))?;
 
-        self.templates.insert(name.to_owned(), template);
+        self.templates.insert(name.to_owned(), Arc::new(template));
+
+        Ok(())
+    }
+
+    /// Compile and store every file beneath the given directory as a [`Template`].
+    ///
+    /// Each `Template` is named after the path of its file relative to the directory,
+    /// using `/` as the separator, so a file at `layouts/base.html` can be extended
+    /// with `(* extends "layouts/base.html" *)`.
+    ///
+    /// Only files ending with one of the given extensions are compiled. When no
+    /// extensions are given, every file is compiled.
+    ///
+    /// If a `Template` with the same name already exists in the [`Engine`],
+    /// it is overwritten.
+    ///
+    /// Files are read once, so a `Template` stored this way is not reloaded when
+    /// [`set_reload`][`Engine::set_reload`] is enabled. Use a
+    /// [`FileLoader`][`crate::load::FileLoader`] for templates that should reload.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when the directory or a file within it cannot be read,
+    /// or when compilation fails. A compilation `Error` carries the name of the
+    /// file that caused it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ban::Engine;
+    ///
+    /// let mut engine = Engine::default();
+    /// engine.insert_directory("templates", &["html"]).unwrap();
+    ///
+    /// let template = engine.get_template("layouts/base.html");
+    /// ```
+    pub fn insert_directory<P>(&mut self, path: P, extensions: &[&str]) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        for (name, text) in load::read_directory(path.as_ref(), extensions)? {
+            self.insert_template_must(&name, &text)?;
+        }
 
         Ok(())
     }
 
+    /// Compile and store every file beneath the given directory as a [`Template`].
+    ///
+    /// Returns the [`Engine`], so additional methods may be chained.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when the directory or a file within it cannot be read,
+    /// or when compilation fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ban::Engine;
+    ///
+    /// let engine = Engine::default()
+    ///     .with_directory("templates", &["html", "txt"])
+    ///     .unwrap();
+    /// ```
+    pub fn with_directory<P>(mut self, path: P, extensions: &[&str]) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        self.insert_directory(path, extensions)?;
+
+        Ok(self)
+    }
+
     /// Return the named [`Template`].
     ///
     /// # Examples
@@ -253,205 +477,799 @@ impl Engine {
     /// assert!(template.is_some());
     /// ```
     pub fn get_template(&self, name: &str) -> Option<&Template> {
-        self.templates.get(name)
+        self.templates.get(name).map(|template| template.as_ref())
     }
 
-    /// Add a [`Filter`].
+    /// Return the named [`Template`], consulting the [`Loader`] if the `Template`
+    /// is not stored in the [`Engine`].
+    ///
+    /// A `Template` returned by the `Loader` is compiled on first use and cached,
+    /// so later calls return the same `Template`.
     ///
     /// # Errors
     ///
-    /// If a `Filter` with the given name already exists in the engine, an [`Error`] is returned.
+    /// Returns an [`Error`] when no `Template` with the given name exists, the `Loader`
+    /// returns an `Error`, or compiling the source returned by the `Loader` fails.
     ///
     /// # Examples
     ///
     /// ```
     /// use std::collections::HashMap;
     ///
-    /// use ban::{
-    ///     filter::{
-    ///         serde::{json, Value},
-    ///         Error,
-    ///     },
-    ///     Engine
-    /// };
+    /// use ban::{Engine, Store};
     ///
-    /// fn to_lowercase(value: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
-    ///     match value {
-    ///         Value::String(string) => Ok(json!(string.to_owned().to_lowercase())),
-    ///         _ => Err(Error::build("filter `to_lowercase` requires string input")
-    ///            .with_help("use quotes to coerce data to string")
-    ///         ),
-    ///     }
-    /// };
+    /// let mut sources = HashMap::new();
+    /// sources.insert("page".to_string(), "hello, (( name ))!".to_string());
     ///
-    /// let mut engine = Engine::default();
-    /// let result = engine.add_filter("to_lowercase", to_lowercase);
+    /// let engine = Engine::default().with_loader(sources);
+    /// let template = engine.load_template("page").unwrap();
+    /// let result = engine.render(&template, &Store::new().with_must("name", "taylor"));
     ///
-    /// assert!(result.is_ok());
+    /// assert_eq!(result.unwrap(), "hello, taylor!");
+    /// assert!(engine.load_template("ghost").is_err());
     /// ```
-    pub fn add_filter<T>(&mut self, name: &str, filter: T) -> Result<(), Error>
-    where
-        T: Filter + 'static,
-    {
-        let as_string = name.to_string();
-        if self.filters.get(&as_string).is_some() {
-            return Err(Error::build(INVALID_FILTER).with_help(format!(
-                "filter with name `{name}` already exists in engine, \
-                overwrite it with `.add_filter_must`"
-            )));
-        }
-        self.filters.insert(as_string, Box::new(filter));
-
-        Ok(())
+    pub fn load_template(&self, name: &str) -> Result<Arc<Template>, Error> {
+        self.resolve_template(name)?
+            .ok_or_else(|| error_missing_template(name))
     }
 
-    /// Add a [`Filter`].
+    /// Set the [`Loader`] consulted when a [`Template`] is not stored in the [`Engine`].
     ///
-    /// If a `Filter` with the given name already exists in the [`Engine`], it is overwritten.
+    /// Any `Template` cached from a previous `Loader` is discarded.
     ///
     /// # Examples
     ///
+    /// ```no_run
+    /// use ban::{load::FileLoader, Engine};
+    ///
+    /// let mut engine = Engine::default();
+    /// engine.set_loader(FileLoader::new("templates"));
     /// ```
-    /// use std::collections::HashMap;
+    pub fn set_loader<T>(&mut self, loader: T)
+    where
+        T: Loader + 'static,
+    {
+        self.loader = Some(Box::new(loader));
+        self.cache
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// Set the [`Loader`] consulted when a [`Template`] is not stored in the [`Engine`].
     ///
-    /// use ban::{
-    ///     filter::{
-    ///         serde::{json, Value},
-    ///         Error,
-    ///     },
-    ///     Engine
-    /// };
+    /// Returns the [`Engine`], so additional methods may be chained.
     ///
-    /// fn to_lowercase(value: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
-    ///     match value {
-    ///         Value::String(string) => Ok(json!(string.to_owned().to_lowercase())),
-    ///         _ => Err(Error::build("filter `to_lowercase` requires string input")
-    ///            .with_help("use quotes to coerce data to string")
-    ///         ),
-    ///     }
-    /// };
+    /// # Examples
     ///
-    /// let mut engine = Engine::default();
-    /// let result = engine.add_filter_must("to_lowercase", to_lowercase);
+    /// ```no_run
+    /// use ban::{load::FileLoader, Engine};
+    ///
+    /// let engine = Engine::default().with_loader(FileLoader::new("templates"));
     /// ```
     #[inline]
-    pub fn add_filter_must<T>(&mut self, name: &str, filter: T)
+    pub fn with_loader<T>(mut self, loader: T) -> Self
     where
-        T: Filter + 'static,
+        T: Loader + 'static,
     {
-        self.filters.insert(name.to_string(), Box::new(filter));
+        self.set_loader(loader);
+
+        self
     }
 
-    /// Add a [`Filter`].
+    /// Enable or disable reloading of [`Template`] instances returned by the [`Loader`].
     ///
-    /// Returns the [`Engine`], so additional methods may be chained.
+    /// When enabled, the [`Engine`] asks the `Loader` when the source of a cached
+    /// `Template` was last [`modified`][`Loader::modified`] each time the `Template`
+    /// is rendered, extended or included, and compiles it again if the source changed.
     ///
-    /// # Errors
+    /// Templates refer to one another by name, so a `Template` that extends or includes
+    /// a changed `Template` renders the new version without being compiled again itself.
     ///
-    /// If a `Filter` with the given name already exists in the engine, an [`Error`] is returned.
+    /// Only templates returned by the `Loader` are reloaded. Templates stored with
+    /// [`insert_directory`][`Engine::insert_directory`] or any other method that
+    /// stores a `Template` directly are never reloaded, so use a
+    /// [`FileLoader`][`crate::load::FileLoader`] to reload templates from disk.
+    ///
+    /// Reloading is disabled by default, and intended for development.
     ///
     /// # Examples
     ///
+    /// ```no_run
+    /// use ban::{load::FileLoader, Engine};
+    ///
+    /// let mut engine = Engine::default().with_loader(FileLoader::new("templates"));
+    /// engine.set_reload(true);
     /// ```
-    /// use std::collections::HashMap;
+    #[inline]
+    pub fn set_reload(&mut self, reload: bool) {
+        self.reload = reload;
+    }
+
+    /// Enable or disable reloading of [`Template`] instances returned by the [`Loader`].
     ///
-    /// use ban::{
-    ///     filter::{
-    ///         serde::{json, Value},
-    ///         Error,
-    ///     },
-    ///     Engine
-    /// };
+    /// Returns the [`Engine`], so additional methods may be chained.
     ///
-    /// fn to_lowercase(value: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
-    ///     match value {
-    ///         Value::String(string) => Ok(json!(string.to_owned().to_lowercase())),
-    ///         _ => Err(Error::build("filter `to_lowercase` requires string input")
-    ///            .with_help("use quotes to coerce data to string")
-    ///         ),
-    ///     }
-    /// };
+    /// # Examples
     ///
-    /// let result = Engine::default().with_filter("to_lowercase", to_lowercase);
+    /// ```no_run
+    /// use ban::{load::FileLoader, Engine};
     ///
-    /// assert!(result.is_ok());
+    /// let engine = Engine::default()
+    ///     .with_loader(FileLoader::new("templates"))
+    ///     .with_reload(true);
     /// ```
     #[inline]
-    pub fn with_filter<T>(mut self, name: &str, filter: T) -> Result<Self, Error>
-    where
-        T: Filter + 'static,
-    {
-        self.add_filter(name, filter)?;
+    pub fn with_reload(mut self, reload: bool) -> Self {
+        self.set_reload(reload);
 
-        Ok(self)
+        self
     }
 
-    /// Add a Filter.
-    ///
-    /// Returns the Engine, so additional methods may be chained.
+    /// Set the [`Escape`] mode, which determines when the output of expressions
+    /// is escaped for HTML.
     ///
-    /// If a Filter with the given name already exists in the engine, it is overwritten.
+    /// The default mode is [`Escape::Auto`], which escapes expressions in templates
+    /// with a name ending in `.html`, `.htm` or `.xml`.
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::collections::HashMap;
+    /// use ban::{Engine, Escape, Store};
     ///
-    /// use ban::{
-    ///     filter::{
-    ///         serde::{json, Value},
-    ///         Error,
-    ///     },
-    ///     Engine
-    /// };
+    /// let mut engine = Engine::default();
+    /// engine.set_escape(Escape::Html);
     ///
-    /// fn to_lowercase(value: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
-    ///     match value {
-    ///         Value::String(string) => Ok(json!(string.to_owned().to_lowercase())),
-    ///         _ => Err(Error::build("filter `to_lowercase` requires string input")
-    ///            .with_help("use quotes to coerce data to string")
-    ///         ),
-    ///     }
-    /// };
+    /// let template = engine.compile_must("(( name ))");
+    /// let result = engine.render(&template, &Store::new().with_must("name", "<b>"));
     ///
-    /// let engine = Engine::default().with_filter_must("to_lowercase", to_lowercase);
+    /// assert_eq!(result.unwrap(), "&lt;b&gt;");
     /// ```
     #[inline]
-    pub fn with_filter_must<T>(mut self, name: &str, filter: T) -> Self
-    where
-        T: Filter + 'static,
-    {
-        self.add_filter_must(name, filter);
+    pub fn set_escape(&mut self, escape: Escape) {
+        self.escape = escape;
+    }
+
+    /// Set the [`Escape`] mode, which determines when the output of expressions
+    /// is escaped for HTML.
+    ///
+    /// Returns the [`Engine`], so additional methods may be chained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ban::{Engine, Escape};
+    ///
+    /// let engine = Engine::default().with_escape(Escape::Never);
+    /// ```
+    #[inline]
+    pub fn with_escape(mut self, escape: Escape) -> Self {
+        self.set_escape(escape);
+
         self
     }
 
-    /// Return the filter with the given name, if it exists in Engine.
+    /// Return the [`Escape`] mode of the [`Engine`].
     #[inline]
-    pub fn get_filter(&self, name: &str) -> Option<&Box<dyn Filter>> {
-        self.filters.get(name)
+    pub fn get_escape(&self) -> Escape {
+        self.escape
     }
-}
 
-impl Default for Engine {
-    /// Create a new [`Engine`] with the default `Syntax`.
+    /// Set the [`Undefined`] mode, which determines what happens when a variable
+    /// does not exist.
+    ///
+    /// The default mode is [`Undefined::Shallow`], which returns an [`Error`] when
+    /// the variable itself is missing, but evaluates a missing key within it as null.
+    /// [`Undefined::Strict`] returns an `Error` that points at any missing part.
     ///
     /// # Examples
     ///
     /// ```
-    /// let engine = ban::default();
+    /// use ban::{filter::serde::json, Engine, Store, Undefined};
+    ///
+    /// let mut engine = Engine::default();
+    /// engine.set_undefined(Undefined::Lenient);
+    ///
+    /// let template = engine.compile_must("hello(( user.nmae ))");
+    /// let result = engine.render(&template, &Store::new().with_must("user", json!({})));
+    ///
+    /// assert_eq!(result.unwrap(), "hello");
     /// ```
-    fn default() -> Self {
-        Self {
-            filters: HashMap::new(),
-            templates: HashMap::new(),
-            finder: Finder::new(Builder::new().to_syntax(), Kind::AhoCorasick),
-        }
+    #[inline]
+    pub fn set_undefined(&mut self, undefined: Undefined) {
+        self.undefined = undefined;
     }
-}
 
-/// Return a String with capacity to suit the given [`Template`].
-///
-/// If the `Template` is extended, a buffer with no capacity is returned,
+    /// Set the [`Undefined`] mode, which determines what happens when a variable
+    /// does not exist.
+    ///
+    /// Returns the [`Engine`], so additional methods may be chained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ban::{Engine, Undefined};
+    ///
+    /// let engine = Engine::default().with_undefined(Undefined::Debug);
+    /// ```
+    #[inline]
+    pub fn with_undefined(mut self, undefined: Undefined) -> Self {
+        self.set_undefined(undefined);
+
+        self
+    }
+
+    /// Return the [`Undefined`] mode of the [`Engine`].
+    #[inline]
+    pub fn get_undefined(&self) -> Undefined {
+        self.undefined
+    }
+
+    /// Enable or disable coercion of strings that contain a number when they are
+    /// compared with a number.
+    ///
+    /// When enabled, `1 == "1"` is true and `"10" > 9` is true. When disabled,
+    /// values of different types are never equal and cannot be ordered.
+    ///
+    /// Coercion is disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ban::{Engine, Store};
+    ///
+    /// let mut engine = Engine::default();
+    /// engine.set_coerce(true);
+    ///
+    /// let template = engine.compile_must("(* if page == \"2\" *)second(* end *)");
+    /// let result = engine.render(&template, &Store::new().with_must("page", 2));
+    ///
+    /// assert_eq!(result.unwrap(), "second");
+    /// ```
+    #[inline]
+    pub fn set_coerce(&mut self, coerce: bool) {
+        self.coerce = coerce;
+    }
+
+    /// Enable or disable coercion of strings that contain a number when they are
+    /// compared with a number.
+    ///
+    /// Returns the [`Engine`], so additional methods may be chained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ban::Engine;
+    ///
+    /// let engine = Engine::default().with_coerce(true);
+    /// ```
+    #[inline]
+    pub fn with_coerce(mut self, coerce: bool) -> Self {
+        self.set_coerce(coerce);
+
+        self
+    }
+
+    /// Return true if the [`Engine`] coerces strings that contain a number when
+    /// they are compared with a number.
+    #[inline]
+    pub fn get_coerce(&self) -> bool {
+        self.coerce
+    }
+
+    /// Return the named [`Template`] from the [`Engine`], or from the [`Loader`]
+    /// if the `Engine` does not have it.
+    ///
+    /// Returns [`None`] when no `Template` with the given name exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when the `Loader` returns an `Error`, or compiling the
+    /// source returned by the `Loader` fails.
+    pub(crate) fn resolve_template(&self, name: &str) -> Result<Option<Arc<Template>>, Error> {
+        if let Some(template) = self.templates.get(name) {
+            return Ok(Some(template.clone()));
+        }
+        let loader = match &self.loader {
+            Some(loader) => loader,
+            None => return Ok(None),
+        };
+
+        let modified = self.reload.then(|| loader.modified(name)).flatten();
+        if let Some(cached) = self
+            .cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+        {
+            if !self.reload || cached.modified == modified {
+                return Ok(Some(cached.template.clone()));
+            }
+        }
+
+        let text = match loader.load(name).map_err(|error| match error.get_name() {
+            Some(_) => error,
+            None => error.with_name(name),
+        })? {
+            Some(text) => text,
+            // A removed template stays cached, so rendering it again while
+            // reloading keeps reporting that it is missing.
+            None => return Ok(None),
+        };
+        let template = Arc::new(
+            Parser::new(&text, &self.finder)
+                .compile(Some(name.to_owned()))
+                .map_err(|error| error.with_name(name))?,
+        );
+
+        self.cache
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                name.to_owned(),
+                Cached {
+                    template: template.clone(),
+                    modified,
+                },
+            );
+
+        Ok(Some(template))
+    }
+
+    /// Return the latest version of the given [`Template`] when reloading is enabled
+    /// and the `Template` was returned by the [`Loader`].
+    ///
+    /// Returns [`None`] when the given `Template` should be rendered as it is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when the `Loader` returns an `Error`, the `Loader` no
+    /// longer has the `Template`, or compiling the source returned by the `Loader`
+    /// fails.
+    fn reload_template(&self, template: &Template) -> Result<Option<Arc<Template>>, Error> {
+        let name = match template.get_name() {
+            Some(name) if self.reload && !self.templates.contains_key(name) => name,
+            _ => return Ok(None),
+        };
+        if !self
+            .cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(name)
+        {
+            return Ok(None);
+        }
+
+        match self.resolve_template(name)? {
+            Some(template) => Ok(Some(template)),
+            None => Err(error_missing_template(name)),
+        }
+    }
+
+    /// Add a [`Filter`].
+    ///
+    /// # Errors
+    ///
+    /// If a `Filter` with the given name already exists in the engine, an [`Error`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use ban::{
+    ///     filter::{
+    ///         serde::{json, Value},
+    ///         Error,
+    ///     },
+    ///     Engine
+    /// };
+    ///
+    /// fn to_lowercase(value: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+    ///     match value {
+    ///         Value::String(string) => Ok(json!(string.to_owned().to_lowercase())),
+    ///         _ => Err(Error::build("filter `to_lowercase` requires string input")
+    ///            .with_help("use quotes to coerce data to string")
+    ///         ),
+    ///     }
+    /// };
+    ///
+    /// let mut engine = Engine::default();
+    /// let result = engine.add_filter("to_lowercase", to_lowercase);
+    ///
+    /// assert!(result.is_ok());
+    /// ```
+    pub fn add_filter<T>(&mut self, name: &str, filter: T) -> Result<(), Error>
+    where
+        T: Filter + 'static,
+    {
+        let as_string = name.to_string();
+        if self.filters.get(&as_string).is_some() {
+            return Err(Error::build(INVALID_FILTER).with_help(format!(
+                "filter with name `{name}` already exists in engine, \
+                overwrite it with `.add_filter_must`"
+            )));
+        }
+        self.filters.insert(as_string, Box::new(filter));
+
+        Ok(())
+    }
+
+    /// Add a [`Filter`].
+    ///
+    /// If a `Filter` with the given name already exists in the [`Engine`], it is overwritten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use ban::{
+    ///     filter::{
+    ///         serde::{json, Value},
+    ///         Error,
+    ///     },
+    ///     Engine
+    /// };
+    ///
+    /// fn to_lowercase(value: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+    ///     match value {
+    ///         Value::String(string) => Ok(json!(string.to_owned().to_lowercase())),
+    ///         _ => Err(Error::build("filter `to_lowercase` requires string input")
+    ///            .with_help("use quotes to coerce data to string")
+    ///         ),
+    ///     }
+    /// };
+    ///
+    /// let mut engine = Engine::default();
+    /// let result = engine.add_filter_must("to_lowercase", to_lowercase);
+    /// ```
+    #[inline]
+    pub fn add_filter_must<T>(&mut self, name: &str, filter: T)
+    where
+        T: Filter + 'static,
+    {
+        self.filters.insert(name.to_string(), Box::new(filter));
+    }
+
+    /// Add a [`Filter`].
+    ///
+    /// Returns the [`Engine`], so additional methods may be chained.
+    ///
+    /// # Errors
+    ///
+    /// If a `Filter` with the given name already exists in the engine, an [`Error`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use ban::{
+    ///     filter::{
+    ///         serde::{json, Value},
+    ///         Error,
+    ///     },
+    ///     Engine
+    /// };
+    ///
+    /// fn to_lowercase(value: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+    ///     match value {
+    ///         Value::String(string) => Ok(json!(string.to_owned().to_lowercase())),
+    ///         _ => Err(Error::build("filter `to_lowercase` requires string input")
+    ///            .with_help("use quotes to coerce data to string")
+    ///         ),
+    ///     }
+    /// };
+    ///
+    /// let result = Engine::default().with_filter("to_lowercase", to_lowercase);
+    ///
+    /// assert!(result.is_ok());
+    /// ```
+    #[inline]
+    pub fn with_filter<T>(mut self, name: &str, filter: T) -> Result<Self, Error>
+    where
+        T: Filter + 'static,
+    {
+        self.add_filter(name, filter)?;
+
+        Ok(self)
+    }
+
+    /// Add a Filter.
+    ///
+    /// Returns the Engine, so additional methods may be chained.
+    ///
+    /// If a Filter with the given name already exists in the engine, it is overwritten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use ban::{
+    ///     filter::{
+    ///         serde::{json, Value},
+    ///         Error,
+    ///     },
+    ///     Engine
+    /// };
+    ///
+    /// fn to_lowercase(value: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+    ///     match value {
+    ///         Value::String(string) => Ok(json!(string.to_owned().to_lowercase())),
+    ///         _ => Err(Error::build("filter `to_lowercase` requires string input")
+    ///            .with_help("use quotes to coerce data to string")
+    ///         ),
+    ///     }
+    /// };
+    ///
+    /// let engine = Engine::default().with_filter_must("to_lowercase", to_lowercase);
+    /// ```
+    #[inline]
+    pub fn with_filter_must<T>(mut self, name: &str, filter: T) -> Self
+    where
+        T: Filter + 'static,
+    {
+        self.add_filter_must(name, filter);
+        self
+    }
+
+    /// Add every [`Filter`] in the standard [`library`][`crate::filter::library`].
+    ///
+    /// A `Filter` with the same name as a standard filter is overwritten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ban::{Engine, Store};
+    ///
+    /// let mut engine = Engine::default();
+    /// engine.add_std_filters();
+    ///
+    /// let template = engine.compile_must("(( name | upper ))");
+    /// let result = engine.render(&template, &Store::new().with_must("name", "taylor"));
+    ///
+    /// assert_eq!(result.unwrap(), "TAYLOR");
+    /// ```
+    #[cfg(feature = "filters")]
+    pub fn add_std_filters(&mut self) {
+        use crate::filter::library::*;
+
+        self.add_filter_must("upper", upper);
+        self.add_filter_must("lower", lower);
+        self.add_filter_must("capitalize", capitalize);
+        self.add_filter_must("trim", trim);
+        self.add_filter_must("replace", replace);
+        self.add_filter_must("truncate", truncate);
+        self.add_filter_must("escape", Safe(escape));
+        self.add_filter_must("length", length);
+        self.add_filter_must("first", first);
+        self.add_filter_must("last", last);
+        self.add_filter_must("reverse", reverse);
+        self.add_filter_must("join", join);
+        self.add_filter_must("default", default);
+        self.add_filter_must("add", add);
+    }
+
+    /// Add every [`Filter`] in the standard [`library`][`crate::filter::library`].
+    ///
+    /// Returns the [`Engine`], so additional methods may be chained.
+    ///
+    /// A `Filter` with the same name as a standard filter is overwritten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ban::Engine;
+    ///
+    /// let engine = Engine::default().with_std_filters();
+    /// ```
+    #[cfg(feature = "filters")]
+    #[inline]
+    pub fn with_std_filters(mut self) -> Self {
+        self.add_std_filters();
+
+        self
+    }
+
+    /// Return the filter with the given name, if it exists in Engine.
+    #[inline]
+    pub fn get_filter(&self, name: &str) -> Option<&Box<dyn Filter>> {
+        self.filters.get(name)
+    }
+
+    /// Add a [`Test`].
+    ///
+    /// # Errors
+    ///
+    /// If a `Test` with the given name already exists in the engine, or the name
+    /// is `defined` or `undefined`, which are reserved, an [`Error`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use ban::{filter::serde::Value, test::Error, Engine};
+    ///
+    /// fn short(value: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    ///     Ok(value.as_str().is_some_and(|string| string.len() < 5))
+    /// }
+    ///
+    /// let mut engine = Engine::default();
+    ///
+    /// assert!(engine.add_test("short", short).is_ok());
+    /// assert!(engine.add_test("even", short).is_err());
+    /// assert!(engine.add_test("defined", short).is_err());
+    /// ```
+    pub fn add_test<T>(&mut self, name: &str, test: T) -> Result<(), Error>
+    where
+        T: Test + 'static,
+    {
+        check_reserved_test(name)?;
+        if self.tests.contains_key(name) {
+            return Err(Error::build(INVALID_TEST).with_help(format!(
+                "test with name `{name}` already exists in engine, \
+                overwrite it with `.add_test_must`"
+            )));
+        }
+        self.tests.insert(name.to_string(), Box::new(test));
+
+        Ok(())
+    }
+
+    /// Add a [`Test`].
+    ///
+    /// If a `Test` with the given name already exists in the [`Engine`], it is overwritten.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is `defined` or `undefined`, which are reserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use ban::{filter::serde::Value, test::Error, Engine};
+    ///
+    /// fn short(value: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    ///     Ok(value.as_str().is_some_and(|string| string.len() < 5))
+    /// }
+    ///
+    /// let mut engine = Engine::default();
+    /// engine.add_test_must("short", short);
+    /// ```
+    #[inline]
+    pub fn add_test_must<T>(&mut self, name: &str, test: T)
+    where
+        T: Test + 'static,
+    {
+        check_reserved_test(name).unwrap();
+        self.tests.insert(name.to_string(), Box::new(test));
+    }
+
+    /// Add a [`Test`].
+    ///
+    /// Returns the [`Engine`], so additional methods may be chained.
+    ///
+    /// # Errors
+    ///
+    /// If a `Test` with the given name already exists in the engine, or the name
+    /// is `defined` or `undefined`, which are reserved, an [`Error`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use ban::{filter::serde::Value, test::Error, Engine};
+    ///
+    /// fn short(value: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    ///     Ok(value.as_str().is_some_and(|string| string.len() < 5))
+    /// }
+    ///
+    /// let result = Engine::default().with_test("short", short);
+    ///
+    /// assert!(result.is_ok());
+    /// ```
+    #[inline]
+    pub fn with_test<T>(mut self, name: &str, test: T) -> Result<Self, Error>
+    where
+        T: Test + 'static,
+    {
+        self.add_test(name, test)?;
+
+        Ok(self)
+    }
+
+    /// Add a [`Test`].
+    ///
+    /// Returns the [`Engine`], so additional methods may be chained.
+    ///
+    /// If a `Test` with the given name already exists in the engine, it is overwritten.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is `defined` or `undefined`, which are reserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use ban::{filter::serde::Value, test::Error, Engine};
+    ///
+    /// fn short(value: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    ///     Ok(value.as_str().is_some_and(|string| string.len() < 5))
+    /// }
+    ///
+    /// let engine = Engine::default().with_test_must("short", short);
+    /// ```
+    #[inline]
+    pub fn with_test_must<T>(mut self, name: &str, test: T) -> Self
+    where
+        T: Test + 'static,
+    {
+        self.add_test_must(name, test);
+
+        self
+    }
+
+    /// Return the test with the given name, if it exists in the [`Engine`].
+    ///
+    /// Always returns [`None`] for `defined` and `undefined`, which are built into
+    /// the renderer rather than registered.
+    #[inline]
+    pub fn get_test(&self, name: &str) -> Option<&dyn Test> {
+        self.tests.get(name).map(Box::as_ref)
+    }
+
+    /// Add every [`Test`] in the standard [`library`][`crate::test::library`].
+    fn add_std_tests(&mut self) {
+        use crate::test::library::*;
+
+        self.add_test_must("even", even);
+        self.add_test_must("odd", odd);
+        self.add_test_must("divisible_by", divisible_by);
+        self.add_test_must("empty", empty);
+        self.add_test_must("string", string);
+        self.add_test_must("number", number);
+        self.add_test_must("integer", integer);
+        self.add_test_must("float", float);
+        self.add_test_must("boolean", boolean);
+        self.add_test_must("array", array);
+        self.add_test_must("object", object);
+        self.add_test_must("null", null);
+    }
+}
+
+impl Default for Engine {
+    /// Create a new [`Engine`] with the default `Syntax`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let engine = ban::default();
+    /// ```
+    fn default() -> Self {
+        Self::new(Builder::new().to_syntax())
+    }
+}
+
+/// A [`Template`] compiled from the source text returned by a [`Loader`].
+struct Cached {
+    /// The compiled [`Template`].
+    template: Arc<Template>,
+    /// The time the source of the [`Template`] was last modified, as reported by
+    /// the [`Loader`] when reloading is enabled.
+    modified: Option<SystemTime>,
+}
+
+/// Return a String with capacity to suit the given [`Template`].
+///
+/// If the `Template` is extended, a buffer with no capacity is returned,
 /// otherwise a buffer with a capacity equal to the length of the source
 /// is created.
 pub fn get_buffer(template: &Template) -> String {
@@ -461,11 +1279,43 @@ pub fn get_buffer(template: &Template) -> String {
     }
 }
 
+/// Return an [`Error`] explaining that the named [`Test`] is reserved, if it is
+/// one of the [`RESERVED_TESTS`].
+fn check_reserved_test(name: &str) -> Result<(), Error> {
+    if !RESERVED_TESTS.contains(&name) {
+        return Ok(());
+    }
+
+    Err(Error::build(INVALID_TEST).with_help(format!(
+        "`{name}` is a reserved test that checks whether a variable exists in the store, \
+        choose another name"
+    )))
+}
+
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::{
+        collections::HashMap,
+        env::temp_dir,
+        fs::{create_dir_all, metadata, remove_dir_all, remove_file, write, File},
+        io,
+        ops::Deref,
+        path::{Path, PathBuf},
+        process,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
 
-    use crate::{engine::Engine, log::Error, Store};
+    use crate::{
+        engine::Engine,
+        load::FileLoader,
+        log::Error,
+        render::{error_missing_template, error_write},
+        Store,
+    };
 
     use serde_json::Value;
 
@@ -518,6 +1368,32 @@ mod tests {
         assert!(engine.get_template("template_name").is_some())
     }
 
+    #[test]
+    fn test_add_test() {
+        let mut engine = Engine::default();
+
+        assert!(engine.get_test("even").is_some());
+        assert!(engine.add_test("even", faux_test).is_err());
+        assert!(engine.add_test("faux", faux_test).is_ok());
+        assert_eq!(
+            engine.add_test("undefined", faux_test),
+            Err(Error::build("invalid test").with_help(
+                "`undefined` is a reserved test that checks whether a variable exists \
+                in the store, choose another name"
+            ))
+        );
+        assert!(engine.get_test("undefined").is_none());
+        assert!(engine.get_test("faux").is_some_and(|test| test
+            .apply(&Value::Null, &HashMap::new())
+            .is_ok_and(|passed| passed)));
+    }
+
+    #[test]
+    #[should_panic(expected = "reserved test")]
+    fn test_add_test_must_reserved() {
+        Engine::default().add_test_must("defined", faux_test);
+    }
+
     #[test]
     fn test_add_overwrite() {
         let value = Value::Null;
@@ -534,6 +1410,292 @@ mod tests {
             .is_ok_and(|v| v == Value::String("b".into()))));
     }
 
+    #[test]
+    fn test_insert_directory() {
+        let directory = get_directory(
+            "insert",
+            &[
+                (
+                    "layouts/base.html",
+                    "<main>(* block content *)(* end *)</main>",
+                ),
+                (
+                    "page.html",
+                    r#"(* extends "layouts/base.html" *)(* block content *)(( name ))(* end *)"#,
+                ),
+                ("notes.txt", "ignored"),
+            ],
+        );
+        let engine = Engine::default()
+            .with_directory(&directory, &["html"])
+            .unwrap();
+
+        assert!(engine.get_template("layouts/base.html").is_some());
+        assert!(engine.get_template("notes.txt").is_none());
+        assert_eq!(
+            engine
+                .render(
+                    engine.get_template("page.html").unwrap(),
+                    &Store::new().with_must("name", "taylor")
+                )
+                .unwrap(),
+            "<main>taylor</main>"
+        );
+    }
+
+    #[test]
+    fn test_insert_directory_error_name() {
+        let directory = get_directory("error", &[("partials/broken.html", "(( name")]);
+        let error = Engine::default()
+            .insert_directory(&directory, &[])
+            .unwrap_err();
+
+        assert_eq!(error.get_name(), Some("partials/broken.html"));
+        assert!(Engine::default()
+            .insert_directory(directory.join("ghost"), &[])
+            .is_err());
+    }
+
+    #[test]
+    fn test_loader_cache() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        let engine = Engine::default().with_loader(move |name: &str| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            match name {
+                "partial" => Ok(Some("(( name ))".to_string())),
+                _ => Ok(None),
+            }
+        });
+        let template = engine
+            .compile("(* include partial *), (* include partial *)")
+            .unwrap();
+        let store = Store::new().with_must("name", "taylor");
+
+        assert_eq!(engine.render(&template, &store).unwrap(), "taylor, taylor");
+        assert_eq!(engine.render(&template, &store).unwrap(), "taylor, taylor");
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(engine
+            .render(&engine.compile("(* include ghost *)").unwrap(), &store)
+            .is_err());
+    }
+
+    #[test]
+    fn test_loader_prefers_templates() {
+        let mut sources = HashMap::new();
+        sources.insert("partial".to_string(), "loaded".to_string());
+        let mut engine = Engine::default().with_loader(sources);
+        engine.insert_template_must("partial", "stored").unwrap();
+        let template = engine.compile("(* include partial *)").unwrap();
+
+        assert_eq!(engine.render(&template, &Store::new()).unwrap(), "stored");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_insert_directory_symlink_loop() {
+        let directory = get_directory("symlink", &[("nested/page.html", "hello")]);
+        std::os::unix::fs::symlink(&directory, directory.join("nested/parent")).unwrap();
+        let engine = Engine::default()
+            .with_directory(&directory, &["html"])
+            .unwrap();
+
+        assert!(engine.get_template("nested/page.html").is_some());
+        assert!(engine
+            .get_template("nested/parent/nested/page.html")
+            .is_none());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_insert_directory_symlink_sibling() {
+        let directory = get_directory("sibling", &[("layouts/base.html", "hello")]);
+        std::os::unix::fs::symlink(directory.join("layouts"), directory.join("alias")).unwrap();
+        std::os::unix::fs::symlink(directory.join("layouts"), directory.join("zebra")).unwrap();
+        let engine = Engine::default()
+            .with_directory(&directory, &["html"])
+            .unwrap();
+
+        assert!(engine.get_template("layouts/base.html").is_some());
+        assert!(engine.get_template("alias/base.html").is_none());
+        assert!(engine.get_template("zebra/base.html").is_none());
+    }
+
+    #[test]
+    fn test_file_loader() {
+        let directory = get_directory(
+            "loader",
+            &[
+                (
+                    "layouts/base.html",
+                    "<main>(* block content *)(* end *)</main>",
+                ),
+                (
+                    "page.html",
+                    r#"(* extends "layouts/base.html" *)(* block content *)hello(* end *)"#,
+                ),
+                ("broken.html", "(( name"),
+            ],
+        );
+        let engine = Engine::default()
+            .with_loader(FileLoader::new(directory.to_path_buf()).with_extensions(&["html"]));
+        let page = engine.load_template("page.html").unwrap();
+
+        assert_eq!(
+            engine.render(&page, &Store::new()).unwrap(),
+            "<main>hello</main>"
+        );
+        assert!(engine.load_template("../page.html").is_err());
+        assert!(engine.get_template("page.html").is_none());
+        assert_eq!(
+            engine.load_template("broken.html").unwrap_err().get_name(),
+            Some("broken.html")
+        );
+    }
+
+    #[test]
+    fn test_reload() {
+        let directory = get_directory(
+            "reload",
+            &[
+                ("base.html", "<main>(* block content *)(* end *)</main>"),
+                (
+                    "page.html",
+                    r#"(* extends "base.html" *)(* block content *)one(* end *)"#,
+                ),
+            ],
+        );
+        let engine = Engine::default()
+            .with_loader(FileLoader::new(directory.to_path_buf()))
+            .with_reload(true);
+        let page = engine.load_template("page.html").unwrap();
+        let store = Store::new();
+
+        assert_eq!(engine.render(&page, &store).unwrap(), "<main>one</main>");
+        rewrite(
+            &directory.join("base.html"),
+            "<div>(* block content *)(* end *)</div>",
+        );
+
+        assert_eq!(engine.render(&page, &store).unwrap(), "<div>one</div>");
+        rewrite(
+            &directory.join("page.html"),
+            r#"(* extends "base.html" *)(* block content *)two(* end *)"#,
+        );
+
+        assert_eq!(engine.render(&page, &store).unwrap(), "<div>two</div>");
+    }
+
+    #[test]
+    fn test_reload_removed() {
+        let directory = get_directory("reload-removed", &[("page.html", "one")]);
+        let engine = Engine::default()
+            .with_loader(FileLoader::new(directory.to_path_buf()))
+            .with_reload(true);
+        let page = engine.load_template("page.html").unwrap();
+
+        assert_eq!(engine.render(&page, &Store::new()).unwrap(), "one");
+        remove_file(directory.join("page.html")).unwrap();
+
+        assert_eq!(
+            engine.render(&page, &Store::new()),
+            Err(error_missing_template("page.html"))
+        );
+        assert!(engine
+            .render_to(&page, &Store::new(), &mut String::new())
+            .is_err());
+    }
+
+    #[test]
+    fn test_reload_disabled() {
+        let directory = get_directory("reload-disabled", &[("page.html", "one")]);
+        let engine = Engine::default().with_loader(FileLoader::new(directory.to_path_buf()));
+        let page = engine.load_template("page.html").unwrap();
+
+        assert_eq!(engine.render(&page, &Store::new()).unwrap(), "one");
+        rewrite(&directory.join("page.html"), "two");
+
+        assert_eq!(engine.render(&page, &Store::new()).unwrap(), "one");
+    }
+
+    #[test]
+    fn test_render_to_io() {
+        let engine = Engine::default();
+        let template = engine.compile("hello, (( name ))!").unwrap();
+        let store = Store::new().with_must("name", "taylor");
+        let mut buffer: Vec<u8> = vec![];
+        engine.render_to_io(&template, &store, &mut buffer).unwrap();
+
+        assert_eq!(buffer, b"hello, taylor!");
+        assert_eq!(
+            engine.render_to_io(&template, &store, &mut Broken),
+            Err(error_write().with_help("failed to flush result of render: connection reset"))
+        );
+
+        let store = Store::new().with_must("name", "a".repeat(10_000));
+        assert_eq!(
+            engine.render_to_io(&template, &store, &mut Broken),
+            Err(error_write().with_help("failed to write result of render: connection reset"))
+        );
+    }
+
+    #[test]
+    fn test_render_to_io_buffered() {
+        let engine = Engine::default();
+        let template = engine
+            .compile("(* for i in items *)(( i )), (* end *)")
+            .unwrap();
+        let store = Store::new().with_must("items", Value::from(vec![1; 100]));
+        let mut writer = Counting(0);
+        engine.render_to_io(&template, &store, &mut writer).unwrap();
+
+        assert_eq!(writer.0, 1);
+    }
+
+    #[test]
+    fn test_render_to_io_failed() {
+        let engine = Engine::default();
+        let template = engine
+            .compile("(* for i in items *)(( i )), (* end *)(( missing ))")
+            .unwrap();
+        let store = Store::new().with_must("items", Value::from(vec![1; 100]));
+        let mut buffer: Vec<u8> = vec![];
+
+        assert!(engine.render_to_io(&template, &store, &mut buffer).is_err());
+        assert!(buffer.is_empty());
+    }
+
+    /// An [`io::Write`][`std::io::Write`] that counts calls to `write`, used to
+    /// test Engine.
+    struct Counting(usize);
+
+    impl io::Write for Counting {
+        fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+            self.0 += 1;
+            Ok(buffer.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// An [`io::Write`][`std::io::Write`] that always fails, used to test Engine.
+    struct Broken;
+
+    impl io::Write for Broken {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(
+                io::ErrorKind::ConnectionReset,
+                "connection reset",
+            ))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
     /// A [`Filter`][`crate::filter::Filter`] used to test Engine.
     fn faux_filter_a(_: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
         Ok(Value::String("a".into()))
@@ -543,4 +1705,60 @@ mod tests {
     fn faux_filter_b(_: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
         Ok(Value::String("b".into()))
     }
+
+    /// A [`Test`][`crate::test::Test`] used to test Engine.
+    fn faux_test(_: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    /// Create a fresh directory in the system temporary directory containing
+    /// the given files, which is removed when the returned [`Directory`] is dropped.
+    fn get_directory(name: &str, files: &[(&str, &str)]) -> Directory {
+        let directory = temp_dir().join(format!("ban-{name}-{}", process::id()));
+        let _ = remove_dir_all(&directory);
+
+        for (path, text) in files {
+            let path = directory.join(path);
+            create_dir_all(path.parent().unwrap()).unwrap();
+            write(path, text).unwrap();
+        }
+
+        Directory(directory)
+    }
+
+    /// A temporary directory used to test Engine, removed when dropped.
+    struct Directory(PathBuf);
+
+    impl Deref for Directory {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl AsRef<Path> for Directory {
+        fn as_ref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for Directory {
+        fn drop(&mut self) {
+            let _ = remove_dir_all(&self.0);
+        }
+    }
+
+    /// Overwrite the file at the given path, and move its modification time forward
+    /// so the change is visible on file systems with coarse timestamps.
+    fn rewrite(path: &Path, text: &str) {
+        let modified = metadata(path).unwrap().modified().unwrap() + Duration::from_secs(1);
+        write(path, text).unwrap();
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
 }
@@ -0,0 +1,295 @@
+//! Contains the [`Loader`] trait, and types that implement `Loader`.
+//!
+//! A `Loader` is consulted by an [`Engine`][`crate::Engine`] when a template
+//! asks for another [`Template`][`crate::Template`] by name, through `include`
+//! or `extends`, and the `Engine` does not already have a `Template` with that
+//! name.
+//!
+//! The source text returned by the `Loader` is compiled on first use, and the
+//! compiled `Template` is cached within the `Engine` so later renders do not
+//! need to compile it again.
+//!
+//! Any struct that implements the `Loader` trait, or function matching the
+//! [`load`][`Loader::load`] method, can be used as a `Loader`.
+//!
+//! When reloading is enabled with [`set_reload`][`crate::Engine::set_reload`], the
+//! `Engine` asks the `Loader` when a cached `Template` was last
+//! [`modified`][`Loader::modified`] before using it, and compiles it again if the
+//! source changed.
+//!
+//! ## Examples
+//!
+//! Loading templates lazily from a directory with [`FileLoader`]:
+//!
+//! ```no_run
+//! use ban::{load::FileLoader, Engine};
+//!
+//! let engine = Engine::default()
+//!     .with_loader(FileLoader::new("templates").with_extensions(&["html"]));
+//! ```
+//!
+//! Loading templates from memory with a function:
+//!
+//! ```
+//! use ban::{filter::Error, Engine, Store};
+//!
+//! fn embedded(name: &str) -> Result<Option<String>, Error> {
+//!     match name {
+//!         "greeting" => Ok(Some("hello, (( name ))!".to_string())),
+//!         _ => Ok(None),
+//!     }
+//! }
+//!
+//! let engine = Engine::default().with_loader(embedded);
+//! let template = engine.compile("(* include greeting *)").unwrap();
+//! let result = engine.render(&template, &Store::new().with_must("name", "taylor"));
+//!
+//! assert_eq!(result.unwrap(), "hello, taylor!");
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{canonicalize, metadata, read_dir, read_to_string},
+    path::{Component, Path, PathBuf, MAIN_SEPARATOR},
+    time::SystemTime,
+};
+
+use crate::{log::Error, path::has_extension};
+
+const INVALID_DIRECTORY: &str = "invalid directory";
+const INVALID_FILE: &str = "invalid template file";
+
+/// Describes a type that can provide the source text of a
+/// [`Template`][`crate::Template`] by name.
+pub trait Loader: Sync + Send {
+    /// Return the source text of the named [`Template`][`crate::Template`],
+    /// or [`None`] if the [`Loader`] has no `Template` with that name.
+    ///
+    /// # Errors
+    ///
+    /// May return an [`Error`] to abort template rendering.
+    fn load(&self, name: &str) -> Result<Option<String>, Error>;
+
+    /// Return the time that the source text of the named
+    /// [`Template`][`crate::Template`] was last modified.
+    ///
+    /// An [`Engine`][`crate::Engine`] with reloading enabled compiles a cached
+    /// `Template` again when this value changes.
+    ///
+    /// Returns [`None`] by default, meaning the source never changes.
+    fn modified(&self, _name: &str) -> Option<SystemTime> {
+        None
+    }
+}
+
+/// Allows any function with a matching signature to be used as a [`Loader`].
+impl<F> Loader for F
+where
+    F: Fn(&str) -> Result<Option<String>, Error> + Sync + Send,
+{
+    fn load(&self, name: &str) -> Result<Option<String>, Error> {
+        self(name)
+    }
+}
+
+/// Allows a map of names to source text to be used as a [`Loader`].
+impl Loader for HashMap<String, String> {
+    fn load(&self, name: &str) -> Result<Option<String>, Error> {
+        Ok(self.get(name).cloned())
+    }
+}
+
+/// A [`Loader`] that reads templates from files beneath a directory.
+///
+/// Templates are named after the path of their file relative to the directory,
+/// using `/` as the separator, such as `layouts/base.html`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ban::{load::FileLoader, Engine};
+///
+/// let loader = FileLoader::new("templates").with_extensions(&["html", "txt"]);
+/// let engine = Engine::default().with_loader(loader);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileLoader {
+    /// The directory that template names are relative to.
+    root: PathBuf,
+    /// The extensions a file must end with to be loaded.
+    ///
+    /// When empty, any file may be loaded.
+    extensions: Vec<String>,
+}
+
+impl FileLoader {
+    /// Create a new [`FileLoader`] over the given directory.
+    ///
+    /// The `FileLoader` will load any file until extensions are set with
+    /// `set_extensions`.
+    #[inline]
+    pub fn new<P>(root: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            root: root.into(),
+            extensions: vec![],
+        }
+    }
+
+    /// Set the extensions a file must end with to be loaded.
+    ///
+    /// Extensions may be given with or without a leading period.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ban::load::FileLoader;
+    ///
+    /// let mut loader = FileLoader::new("templates");
+    /// loader.set_extensions(&["html"]);
+    /// ```
+    pub fn set_extensions(&mut self, extensions: &[&str]) {
+        self.extensions = extensions.iter().map(|e| e.to_string()).collect();
+    }
+
+    /// Set the extensions a file must end with to be loaded.
+    ///
+    /// Returns the [`FileLoader`], so additional methods may be chained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ban::load::FileLoader;
+    ///
+    /// let loader = FileLoader::new("templates").with_extensions(&["html"]);
+    /// ```
+    #[inline]
+    pub fn with_extensions(mut self, extensions: &[&str]) -> Self {
+        self.set_extensions(extensions);
+
+        self
+    }
+
+    /// Return the [`Path`] of the file with the given name, or [`None`] if the
+    /// name would lead outside of the directory, or the file does not have one of
+    /// the expected extensions.
+    fn get_path(&self, name: &str) -> Option<PathBuf> {
+        let relative = Path::new(name);
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return None;
+        }
+
+        let extensions: Vec<&str> = self.extensions.iter().map(|e| e.as_str()).collect();
+        if !has_extension(relative, &extensions) {
+            return None;
+        }
+
+        Some(self.root.join(relative))
+    }
+}
+
+impl Loader for FileLoader {
+    fn load(&self, name: &str) -> Result<Option<String>, Error> {
+        let path = match self.get_path(name) {
+            Some(path) if path.is_file() => path,
+            _ => return Ok(None),
+        };
+
+        read_to_string(&path)
+            .map(Some)
+            .map_err(|error| error_io(INVALID_FILE, &path, error))
+    }
+
+    fn modified(&self, name: &str) -> Option<SystemTime> {
+        metadata(self.get_path(name)?).ok()?.modified().ok()
+    }
+}
+
+/// Return the name and source text of every file beneath the given directory.
+///
+/// Each name is the path of the file relative to `root`, using `/` as the
+/// separator regardless of platform, such as `layouts/base.html`.
+///
+/// When `extensions` is empty, every file is returned. Otherwise, only files
+/// with a matching extension are returned. Extensions may be given with or
+/// without a leading period.
+///
+/// The result is sorted by name.
+///
+/// Symbolic links to directories are followed, but each directory is only read
+/// once, so a link to a parent directory does not cause an endless walk. Linked
+/// directories are read after every other directory, so a file that can be
+/// reached without following a link is always named by that path.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if a directory or file cannot be read.
+pub(crate) fn read_directory(
+    root: &Path,
+    extensions: &[&str],
+) -> Result<Vec<(String, String)>, Error> {
+    let mut files = vec![];
+    let mut pending = vec![root.to_path_buf()];
+    let mut linked = vec![];
+    let mut visited = HashSet::new();
+
+    while let Some(directory) = pending.pop().or_else(|| linked.pop()) {
+        let canonical = canonicalize(&directory)
+            .map_err(|error| error_io(INVALID_DIRECTORY, &directory, error))?;
+        if !visited.insert(canonical) {
+            continue;
+        }
+        let mut entries = read_dir(&directory)
+            .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+            .map_err(|error| error_io(INVALID_DIRECTORY, &directory, error))?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let path = entry.path();
+            if path.is_dir() {
+                let file_type = entry
+                    .file_type()
+                    .map_err(|error| error_io(INVALID_DIRECTORY, &path, error))?;
+                if file_type.is_symlink() {
+                    linked.push(path);
+                } else {
+                    pending.push(path);
+                }
+                continue;
+            }
+            if !has_extension(&path, extensions) {
+                continue;
+            }
+
+            let text =
+                read_to_string(&path).map_err(|error| error_io(INVALID_FILE, &path, error))?;
+            files.push((get_name(root, &path), text));
+        }
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(files)
+}
+
+/// Return the name of a file relative to the given root, using `/` as the
+/// separator.
+fn get_name(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path).to_string_lossy();
+
+    if MAIN_SEPARATOR == '/' {
+        relative.into_owned()
+    } else {
+        relative.replace(MAIN_SEPARATOR, "/")
+    }
+}
+
+/// Return an [`Error`] with the given title describing a failure to read the
+/// given [`Path`].
+pub(crate) fn error_io(title: &str, path: &Path, error: std::io::Error) -> Error {
+    Error::build(title).with_help(format!("unable to read `{}`: {error}", path.display()))
+}
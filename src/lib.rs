@@ -8,7 +8,10 @@
 //! - Common logical constructs (`if`, `let`, and `for`).
 //! - User-defined filters to transform content.
 //!     - An optional standard library providing filters for common
-//!     functionality, like HTML escaping.
+//!     functionality, like HTML escaping, enabled by the `filters` feature.
+//! - User-defined tests to check values in conditions, like `(* if n is even *)`.
+//! - Macros with parameters, to reuse parts of a template.
+//!     - Import - share the macros of one template with others.
 //! - Multiple strategies for template inheritance.
 //!     - Block/extends - divide a template up into blocks that can be
 //!     overridden by child templates.
@@ -37,6 +40,16 @@
 //! The [`Engine`] type provides a place for you to register filters and store other
 //! templates that you can call with the `include` block.
 //!
+//! Templates can be stored one at a time with
+//! [`insert_template`][`crate::Engine::insert_template`], or compiled from every file
+//! in a directory with [`insert_directory`][`crate::Engine::insert_directory`]. Each file
+//! is named after its path relative to the directory, like `layouts/base.html`.
+//!
+//! To avoid compiling every template up front, give the `Engine` a
+//! [`Loader`][`crate::load::Loader`] instead. The `Engine` consults it whenever a
+//! template it does not have is requested, then compiles and caches the result.
+//! See the [`load`][`crate::load`] module for more information.
+//!
 //! ## Compile
 //!
 //! Use the `Engine` to compile a [`Template`].
@@ -97,12 +110,48 @@
 //! (( name ))
 //! ```
 //!
+//! Nested values are reached with a period, or with brackets when the key is
+//! an integer, a string or another variable. A negative integer counts back
+//! from the end of an array:
+//!
+//! ```text
+//! (( user.name )) (( items.0 )) (( items[-1] )) (( prices[product.id] ))
+//! ```
+//!
 //! Or, if you want to mutate the "name" variable using filters:
 //!
 //! ```text
 //! (( name | to_lowercase | left 3 ))
 //! ```
 //!
+//! Values may be combined with the arithmetic operators `+`, `-`, `*` and `/`,
+//! anywhere a variable or literal is accepted. Multiplication and division are
+//! applied first, unless parentheses say otherwise:
+//!
+//! ```text
+//! (( price * (quantity + 1) ))
+//! ```
+//!
+//! `+` will also join two strings or two arrays, and merge two objects.
+//!
+//! An inline `if` chooses between two values, using the same conditions as an
+//! [`if`](#if) block. Without an `else`, the value is null:
+//!
+//! ```text
+//! <li class="(( "active" if page == current else "" ))">
+//! ```
+//!
+//! The words `true`, `false` and `null` are always literal values, so they
+//! cannot be used as the name of a variable. A nested key with one of these
+//! names can still be reached with brackets, like `(( flags["null"] ))`.
+//!
+//! Arrays and objects can be written directly in a template, and may contain
+//! variables. Object keys may be identifiers or strings:
+//!
+//! ```text
+//! (* include card tags: ["new", product.category], size: { width: 2, "max height": 4 } *)
+//! ```
+//!
 //! ## Filters
 //!
 //! [`Filters`][`crate::filter::Filter`] can be used in expressions to
@@ -129,8 +178,51 @@
 //!
 //! Both variants require arguments to be separated with a comma.
 //!
+//! The standard filters, like `upper`, `join` and `default`, can be added to
+//! an `Engine` with [`with_std_filters`][`crate::Engine::with_std_filters`].
+//!
 //! See the [`filter`][`crate::filter`] module for more information.
 //!
+//! ## Escaping
+//!
+//! The output of expressions in templates with a name ending in `.html`,
+//! `.htm` or `.xml` is escaped for HTML, so characters like `<` and `&` are
+//! rendered as `&lt;` and `&amp;`. Raw text is never escaped.
+//!
+//! End an expression with the `safe` filter to render a value as-is:
+//!
+//! ```text
+//! (( markup | safe ))
+//! ```
+//!
+//! A value that is always safe, like HTML that was already sanitized, can be
+//! marked in the store with [`set_safe`][`crate::Store::set_safe`] instead.
+//! String literals and the output of macros are not escaped again, but only
+//! the final filter in an expression decides whether its output is safe, so
+//! `(( icon() | trim ))` is escaped.
+//!
+//! Use [`set_escape`][`crate::Engine::set_escape`] to escape every template,
+//! or none of them.
+//!
+//! ## Undefined
+//!
+//! By default, rendering fails when a variable is missing from the store, such
+//! as the `user` in `(( user.name ))`, while a missing key within a variable,
+//! such as the `nmae` in `(( user.nmae ))`, is null and renders as nothing.
+//!
+//! Use [`set_undefined`][`crate::Engine::set_undefined`] with
+//! [`Undefined::Strict`] to fail when any part of a variable is missing, with
+//! an error that points at that part, [`Undefined::Lenient`] to render every
+//! missing value as nothing, or [`Undefined::Debug`] to render a placeholder
+//! like `[undefined: user.nmae]`.
+//!
+//! Values that are expected to be missing at times can be given a fallback
+//! with `??`, which is used when the value on its left is missing or null:
+//!
+//! ```text
+//! (( user.nickname ?? user.name ?? "anon" ))
+//! ```
+//!
 //! ## If
 //!
 //! If blocks allow conditional rendering based on a series of expressions.
@@ -152,6 +244,23 @@
 //! (* end *)
 //! ```
 //!
+//! Values of different types are never equal, so `null` is only equal to
+//! `null`, as in `(* if user.email == null *)`, and only values of the same
+//! type can be ordered with `>` or `<`.
+//! Strings that contain a number can be compared with numbers by enabling
+//! [`Engine::set_coerce`].
+//!
+//! Arrays and objects are equal when their contents are equal. Arrays are
+//! ordered by their first unequal element, while objects cannot be ordered;
+//! compare their sizes with the `length` filter instead:
+//!
+//! ```text
+//! (* let size = settings | length *)
+//! (* if size > 2 *)
+//!     hello
+//! (* end *)
+//! ```
+//!
 //! Here's a cheatsheet for truthy values:
 //!
 //! Type    | Truthy When
@@ -170,6 +279,33 @@
 //! (* end *)
 //! ```
 //!
+//! Use `in` to check whether an array contains an element, an object contains
+//! a key, or a string contains a substring, and `not in` to check the opposite:
+//!
+//! ```text
+//! (* if role in allowed_roles && "@" not in name *)
+//!     hello
+//! (* end *)
+//! ```
+//!
+//! Use `is` to apply a [`test`][`crate::test`] to a value, and `is not` to
+//! negate it. Every engine has tests like `defined`, `even` and `empty`:
+//!
+//! ```text
+//! (* if user is defined && user.tags is not empty *)
+//!     hello
+//! (* end *)
+//! ```
+//!
+//! `&&` is applied before `||`, and `not` applies only to the comparison that
+//! follows it. Use parentheses to group conditions differently:
+//!
+//! ```text
+//! (* if is_admin && not (banned || suspended) *)
+//!     hello
+//! (* end *)
+//! ```
+//!
 //! ### Examples
 //!
 //! ```
@@ -216,6 +352,59 @@
 //! Array  | Index of element.   | Element of array.
 //! Object | Object key.         | Object value.
 //!
+//! Each iteration also has access to a `loop` object:
+//!
+//! Property   | Value
+//! ---------- | -----
+//! `index`    | 1-based index of the iteration.
+//! `index0`   | 0-based index of the iteration.
+//! `first`    | True on the first iteration.
+//! `last`     | True on the last iteration.
+//! `length`   | Number of iterations.
+//! `revindex` | 1-based index of the iteration, counting from the end.
+//! `parent`   | The `loop` object of the enclosing `for` block, or null.
+//!
+//! ```text
+//! (* for item in inventory *)
+//!     (( loop.index )) of (( loop.length )): (( item.name ))
+//! (* end *)
+//! ```
+//!
+//! An `else` block is rendered instead when there is nothing to iterate on:
+//!
+//! ```text
+//! (* for item in inventory *)
+//!     Name: (( item.name ))
+//! (* else *)
+//!     Your inventory is empty.
+//! (* end *)
+//! ```
+//!
+//! Ranges of integers can be iterated on without creating an array. The end
+//! of `start..end` is excluded, while the end of `start..=end` is included,
+//! and an optional `step` sets the difference between each integer:
+//!
+//! ```text
+//! (* for i in 0..count *)(( i ))(* end *)
+//! (* for i in 10..=0 step -2 *)(( i ))(* end *)
+//! ```
+//!
+//! The bounds and step of a range must be integers, and the step must not be
+//! zero. A `for` block counts through a range without storing it, but outside
+//! of a `for` block a range evaluates to an array, which may hold at most
+//! 100,000 integers.
+//!
+//! Use `break` to stop the loop early, or `continue` to skip to the next item.
+//! Both may appear within an `if` block inside of the `for` block:
+//!
+//! ```text
+//! (* for item in inventory *)
+//!     (* if item.hidden *)(* continue *)(* end *)
+//!     (* if loop.index > 10 *)(* break *)(* end *)
+//!     Name: (( item.name ))
+//! (* end *)
+//! ```
+//!
 //! ### Examples
 //!
 //! ```
@@ -283,6 +472,90 @@
 //! assert_eq!(result, "hello, taylor!");
 //!```
 //!
+//! ## Macro
+//!
+//! Macros are reusable pieces of a template that accept parameters. They are
+//! defined at the top level of a template, and may be called anywhere within it:
+//!
+//! ```text
+//! (* macro button(label, kind="primary") *)
+//!     <button class="(( kind ))">(( label ))</button>
+//! (* end *)
+//!
+//! (( button("Save") ))
+//! (( button("Cancel", kind: "secondary") ))
+//! ```
+//!
+//! Arguments are matched to parameters by position, or by name. A parameter with
+//! a default value may be left out.
+//!
+//! A macro has access to the store, but not to values assigned with `let` outside
+//! of it, and values assigned within a macro are not visible outside of it.
+//! Expressions within a macro are escaped according to the template that calls
+//! it, and the output of a macro is not escaped again when it is rendered.
+//!
+//! Macros may call other macros, or themselves, but rendering fails once calls
+//! are nested more than 32 deep.
+//!
+//! ### Examples
+//!
+//! ```rust
+//! use ban::Store;
+//!
+//! let engine = ban::default();
+//! let template = engine
+//!     .compile(r#"(* macro greet(name, greeting="hello") *)(( greeting )), (( name ))!(* end *)(( greet(user) ))"#)
+//!     .unwrap();
+//!
+//! let store = Store::new().with_must("user", "taylor");
+//! let result = engine.render(&template, &store);
+//!
+//! assert_eq!(result.unwrap(), "hello, taylor!");
+//!```
+//!
+//! ## Import
+//!
+//! Import expressions make the macros of another template available. Every macro
+//! can be imported within a namespace, or only some macros can be imported by name:
+//!
+//! ```text
+//! (* import "forms.html" as forms *)
+//! (* from "forms.html" import input, select *)
+//!
+//! (( forms.label("Name") ))
+//! (( input("name") ))
+//! ```
+//!
+//! Imports must appear at the top level of a template. A macro defined in the
+//! template itself takes precedence over one imported by name with the same name.
+//!
+//! The imported template is found the same way as an included template, and is
+//! only rendered when one of its macros is called. A template that imports itself,
+//! directly or through other templates, causes an error.
+//!
+//! ### Examples
+//!
+//! ```rust
+//! use ban::Store;
+//!
+//! let mut engine = ban::default();
+//! engine
+//!     .insert_template_must(
+//!         "forms.html",
+//!         r#"(* macro input(name) *)<input name="(( name ))">(* end *)"#,
+//!     )
+//!     .unwrap();
+//!
+//! let template = engine
+//!     .compile(r#"(* import "forms.html" as forms *)(( forms.input(field) ))"#)
+//!     .unwrap();
+//!
+//! let store = Store::new().with_must("field", "email");
+//! let result = engine.render(&template, &store);
+//!
+//! assert_eq!(result.unwrap(), r#"<input name="email">"#);
+//!```
+//!
 //! ## Include
 //!
 //! Include expressions allow other templates to be rendered.
@@ -375,6 +648,39 @@
 //! View the [examples/inheritance](https://github.com/jmkng/ban/tree/main/examples/inheritance)
 //! directory for a full illustration.
 //!
+//! ## Raw
+//!
+//! Text between `raw` and `end` is rendered as-is, so it may contain
+//! expressions and blocks that should not be evaluated:
+//!
+//! ```text
+//! (* raw *)Render a name with (( name )).(* end *)
+//! ```
+//!
+//! Blocks inside of the raw text must be closed with their own `end`, so the
+//! `end` that closes the `raw` block can be told apart from them.
+//!
+//! ## Comments
+//!
+//! Comments are removed from the output, along with anything inside of them.
+//! They are disabled by default, so that text like `(#42)` is rendered as-is.
+//! Enable them by giving the `Builder` a pair of comment markers:
+//!
+//! ```
+//! use ban::{Builder, Engine, Store};
+//!
+//! let engine = Engine::new(Builder::new().with_comment("(#", "#)").to_syntax());
+//! let template = engine.compile("hello(# todo: add a name #)!").unwrap();
+//!
+//! assert_eq!(engine.render(&template, &Store::new()).unwrap(), "hello!");
+//! ```
+//!
+//! Like expressions and blocks, comments may trim the surrounding whitespace:
+//!
+//! ```text
+//! (#- todo: add a footer -#)
+//! ```
+//!
 //! ## Delimiters
 //!
 //! Use the `Builder` type to create an `Engine` that recognizes a different
@@ -387,12 +693,13 @@
 //!     Builder::new()
 //!         .with_expression(">>", "<<")
 //!         .with_block("{@", "@}")
+//!         .with_comment("{#", "#}")
 //!         .with_whitespace(&'~')
 //!         .to_syntax(),
 //! );
 //!
 //! let template = engine
-//!     .compile("{@ if true ~@}     Hello, >> name <<!{@ end @}")
+//!     .compile("{@ if true ~@}     Hello, >> name <<!{# greeting #}{@ end @}")
 //!     .unwrap();
 //! let store = Store::new().with_must("name", "taylor");
 //! let result = engine.render(&template, &store).unwrap();
@@ -407,12 +714,13 @@
 mod compile;
 mod engine;
 mod log;
+mod path;
 mod region;
 mod render;
 
 pub use compile::{Builder, Template};
-pub use engine::Engine;
-pub use render::{filter, Store};
+pub use engine::{load, Engine};
+pub use render::{filter, test, Escape, Store, Undefined};
 
 use morel::Syntax;
 
@@ -0,0 +1,17 @@
+use std::path::Path;
+
+/// Return true if the given [`Path`] ends with one of the extensions.
+///
+/// Always returns true when no extensions are given.
+pub(crate) fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    if extensions.is_empty() {
+        return true;
+    }
+
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => extensions
+            .iter()
+            .any(|expect| expect.trim_start_matches('.') == extension),
+        None => false,
+    }
+}
@@ -1,36 +1,61 @@
 pub mod filter;
 pub mod pipe;
+pub mod test;
 
+mod arithmetic;
 mod compare;
+mod escape;
+mod range;
 mod store;
+mod undefined;
 
+pub use escape::Escape;
 pub use store::Store;
+pub use undefined::Undefined;
 
 use std::{
     borrow::Cow,
+    cell::{Cell, RefCell},
     collections::HashMap,
     fmt::{Display, Write},
     mem::take,
+    rc::Rc,
+    sync::Arc,
 };
 
 use crate::{
     compile::{tree::*, Scope, Template},
-    engine::INVALID_FILTER,
+    engine::{INVALID_FILTER, INVALID_TEST, RESERVED_TESTS},
     log::Error,
     region::Region,
     Engine,
 };
 
 use self::{
+    arithmetic::calculate,
     compare::{compare_values, is_truthy},
+    escape::HtmlWriter,
     pipe::Pipe,
+    range::{expect_integer, Sequence},
     store::Shadow,
 };
 
 use serde::Serialize;
-use serde_json::Value;
+use serde_json::{json, Value};
 
 const INCOMPATIBLE_TYPES: &str = "incompatible types";
+const INVALID_INDEX: &str = "invalid index";
+const MISSING_VALUE: &str = "missing store value";
+const INVALID_MACRO: &str = "invalid macro";
+const INVALID_ARGUMENT: &str = "invalid argument";
+const INVALID_IMPORT: &str = "invalid import";
+
+/// The name of the object shadowed in each iteration of a [`For`].
+const LOOP: &str = "loop";
+
+/// The number of [`Macro`] calls that may be nested within one another before
+/// rendering is aborted.
+const MAX_DEPTH: usize = 32;
 
 /// Provides methods to render a set of [`Tree`] against some context data.
 pub struct Renderer<'source, 'store> {
@@ -42,6 +67,19 @@ pub struct Renderer<'source, 'store> {
     shadow: Shadow<'store>,
     /// Blocks available for rendering.
     blocks: BlockMap<'source>,
+    /// When true, the output of expressions is escaped for HTML.
+    escape: bool,
+    /// The location of the first undefined variable found while evaluating
+    /// an [`Output`], when the [`Undefined`] mode is [`Undefined::Debug`].
+    undefined: Cell<Option<Region>>,
+    /// The names of the templates that import the [`Template`] being rendered,
+    /// outermost first, used to detect an import cycle.
+    importers: Vec<String>,
+    /// Imported templates whose imports were already checked, shared with
+    /// each nested [`Renderer`].
+    imported: ImportMap,
+    /// The number of [`Macro`] calls that the [`Renderer`] is nested within.
+    depth: usize,
 }
 
 impl<'source, 'store> Renderer<'source, 'store> {
@@ -52,6 +90,11 @@ impl<'source, 'store> Renderer<'source, 'store> {
             template,
             shadow: Shadow::new(store),
             blocks: HashMap::new(),
+            escape: engine.get_escape().applies(template.get_name()),
+            undefined: Cell::new(None),
+            importers: vec![],
+            imported: Rc::new(RefCell::new(HashMap::new())),
+            depth: 0,
         }
     }
 
@@ -64,17 +107,11 @@ impl<'source, 'store> Renderer<'source, 'store> {
     pub fn render(mut self, pipe: &mut Pipe) -> Result<(), Error> {
         match &self.template.get_extends() {
             Some(extended) => self.evaluate_scope(extended, pipe),
-            None => self.render_scope(self.template.get_scope(), pipe),
+            None => self
+                .render_scope(self.template.get_scope(), pipe)
+                .map(|_| ()),
         }
-        .map_err(|error| {
-            // The `Error` might come from another `Template`, so don't change
-            // the name if it already has one.
-            if !error.get_name().is_some() && self.template.get_name().is_some() {
-                return error.with_name(self.template.get_name().unwrap());
-            }
-
-            error
-        })?;
+        .map_err(|error| name_error(error, self.template))?;
 
         Ok(())
     }
@@ -89,27 +126,28 @@ impl<'source, 'store> Renderer<'source, 'store> {
     /// Returns an [`Error`] if the extended `Template` does not exist, or rendering any
     /// [`Tree`] instance fails.
     fn evaluate_scope(&mut self, extends: &Extends, pipe: &mut Pipe) -> Result<(), Error> {
-        let name = extends
-            .name
-            .get_region()
-            .literal(self.template.get_source());
+        let name = self.evaluate_name(&extends.name);
         let template = self
             .engine
-            .get_template(name)
+            .resolve_template(name)?
             .ok_or_else(|| error_missing_template(name))?;
         self.collect_blocks(self.template.get_scope());
 
         Renderer::new(self.engine, &template, self.shadow.store)
             .with_blocks(take(&mut self.blocks))
+            .with_imported(self.imported.clone())
             .render(pipe)
     }
 
     /// Render a [`Scope`].
     ///
+    /// Returns a [`Flow`] that describes how the enclosing [`For`] should proceed,
+    /// which is [`Flow::Proceed`] unless a `break` or `continue` is rendered.
+    ///
     /// # Errors
     ///
     /// Returns an [`Error`] if rendering any [`Tree`] instance fails.
-    fn render_scope(&mut self, scope: &'source Scope, pipe: &mut Pipe) -> Result<(), Error> {
+    fn render_scope(&mut self, scope: &'source Scope, pipe: &mut Pipe) -> Result<Flow, Error> {
         let mut iterator = scope.data.iter();
         while let Some(next) = iterator.next() {
             match next {
@@ -118,15 +156,33 @@ impl<'source, 'store> Renderer<'source, 'store> {
                     pipe.write_str(value).map_err(|_| error_write())?
                 }
                 Tree::Output(ou) => {
-                    let value = self.evaluate_expression(&ou.expression)?;
-                    pipe.write_value(&value).map_err(|_| error_write())?
+                    self.undefined.set(None);
+                    let mut value = self.evaluate_expression(&ou.expression)?;
+                    if let Some(region) = self.undefined.take().filter(|_| value.is_null()) {
+                        let path = region.literal(self.template.get_source());
+                        value = Cow::Owned(Value::String(format!("[undefined: {path}]")));
+                    }
+                    if self.escape && !self.is_safe(&ou.expression) {
+                        Pipe::new(&mut HtmlWriter::new(pipe)).write_value(&value)
+                    } else {
+                        pipe.write_value(&value)
+                    }
+                    .map_err(|_| error_write())?
                 }
                 Tree::If(i) => {
-                    self.render_if(i, pipe)?;
+                    let flow = self.render_if(i, pipe)?;
+                    if flow != Flow::Proceed {
+                        return Ok(flow);
+                    }
                 }
                 Tree::For(fo) => {
-                    self.render_for(fo, pipe)?;
+                    let flow = self.render_for(fo, pipe)?;
+                    if flow != Flow::Proceed {
+                        return Ok(flow);
+                    }
                 }
+                Tree::Break => return Ok(Flow::Break),
+                Tree::Continue => return Ok(Flow::Continue),
                 Tree::Let(le) => {
                     self.evaluate_let(le)?;
                 }
@@ -136,11 +192,10 @@ impl<'source, 'store> Renderer<'source, 'store> {
                 Tree::Block(bl) => {
                     self.render_block(bl, pipe)?;
                 }
-                _ => unreachable!("parser must catch invalid top level tree"),
             }
         }
 
-        Ok(())
+        Ok(Flow::Proceed)
     }
 
     /// Render a [`Block`] within the [`Renderer`] that matches the name of the given `Block`.
@@ -151,13 +206,15 @@ impl<'source, 'store> Renderer<'source, 'store> {
     ///
     /// Returns an [`Error`] if rendering any [`Tree`] instance fails.
     fn render_block(&mut self, block: &'source Block, pipe: &mut Pipe) -> Result<(), Error> {
-        let name = block.name.get_region().literal(self.template.get_source());
+        let name = self.evaluate_name(&block.name);
 
         match self.blocks.get(name) {
             Some(shadowed) => Renderer::new(self.engine, shadowed.template, self.shadow.store)
+                .with_imported(self.imported.clone())
                 .render_scope(&shadowed.block.scope, pipe),
             None => self.render_scope(&block.scope, pipe),
         }
+        .map(|_| ())
     }
 
     /// Render an [`Include`].
@@ -167,13 +224,10 @@ impl<'source, 'store> Renderer<'source, 'store> {
     /// Returns an [`Error`] if the named [`Template`] is not found in the [`Engine`],
     /// or rendering any [`Tree`] instance fails.
     fn render_include(&mut self, include: &Include, pipe: &mut Pipe) -> Result<(), Error> {
-        let name = include
-            .name
-            .get_region()
-            .literal(self.template.get_source());
+        let name = self.evaluate_name(&include.name);
         let template = self
             .engine
-            .get_template(name)
+            .resolve_template(name)?
             .ok_or_else(|| error_missing_template(name))?;
 
         if include.mount.is_some() {
@@ -184,11 +238,18 @@ impl<'source, 'store> Renderer<'source, 'store> {
                 let name = point.name.literal(self.template.get_source());
                 let value = self.evaluate_base(&point.value)?;
                 scoped_store.insert_must(name, value);
+                if self.is_safe_base(&point.value) {
+                    scoped_store.set_safe(name);
+                }
             }
-            Renderer::new(self.engine, template, &scoped_store).render(pipe)?
+            Renderer::new(self.engine, &template, &scoped_store)
+                .with_imported(self.imported.clone())
+                .render(pipe)?
         } else {
             // Unscoped include, use the same store.
-            Renderer::new(self.engine, template, self.shadow.store).render(pipe)?
+            Renderer::new(self.engine, &template, self.shadow.store)
+                .with_imported(self.imported.clone())
+                .render(pipe)?
         };
 
         Ok(())
@@ -196,107 +257,203 @@ impl<'source, 'store> Renderer<'source, 'store> {
 
     /// Render an [`If`].
     ///
+    /// Returns the [`Flow`] of the rendered [`Scope`].
+    ///
     /// # Errors
     ///
     /// Returns an [`Error`] if a [`Scope`] is chosen to be rendered, but a [`Tree`]
     /// instance within the `Scope` fails to render.
-    fn render_if(&mut self, i: &'source If, pipe: &mut Pipe) -> Result<(), Error> {
-        for branch in i.tree.branches.iter() {
-            if !self.evaluate_branch(branch)? {
-                if i.else_branch.is_some() {
-                    self.render_scope(i.else_branch.as_ref().unwrap(), pipe)?;
-                }
-                return Ok(());
-            }
+    fn render_if(&mut self, i: &'source If, pipe: &mut Pipe) -> Result<Flow, Error> {
+        if self.evaluate_condition(&i.condition)? {
+            return self.render_scope(&i.then_branch, pipe);
         }
 
-        self.render_scope(&i.then_branch, pipe)
+        match &i.else_branch {
+            Some(else_branch) => self.render_scope(else_branch, pipe),
+            None => Ok(Flow::Proceed),
+        }
     }
 
     /// Render a [`For`].
     ///
+    /// Each iteration shadows a `loop` object that describes the iteration, with these
+    /// properties:
+    ///
+    /// - `index`: The 1-based index of the iteration.
+    /// - `index0`: The 0-based index of the iteration.
+    /// - `first`: True on the first iteration.
+    /// - `last`: True on the last iteration.
+    /// - `length`: The number of iterations.
+    /// - `revindex`: The 1-based index of the iteration, counting from the end.
+    /// - `parent`: The `loop` object of the enclosing [`For`], or null.
+    ///
+    /// When there is nothing to iterate on, the else [`Scope`] is rendered instead,
+    /// if the `For` has one, and its [`Flow`] is returned so that a `break` or
+    /// `continue` within it applies to the enclosing `For`.
+    ///
     /// # Errors
     ///
     /// Returns an [`Error`] if the [`Base`] is not found in the [`Store`],
     /// or rendering any [`Tree`] instance fails.
-    fn render_for(&mut self, fo: &'source For, pipe: &mut Pipe) -> Result<(), Error> {
-        self.shadow.push();
+    fn render_for(&mut self, fo: &'source For, pipe: &mut Pipe) -> Result<Flow, Error> {
+        if let Base::Range(range) = &fo.base {
+            let sequence = self.evaluate_range(range)?;
+            let length = sequence.len();
+            let items = sequence
+                .enumerate()
+                .map(|(index, integer)| (json!(index), json!(integer)));
+
+            return self.render_for_items(fo, items, length, pipe);
+        }
 
         let value = self.evaluate_base(&fo.base)?;
-        match value.as_ref() {
-            Value::String(st) => {
-                for (index, char) in st.to_owned().char_indices() {
-                    self.shadow_set(&fo.set, (Some(index), char))?;
-                    self.render_scope(&fo.scope, pipe)?;
-                }
-            }
-            Value::Array(ar) => {
-                for (index, value) in ar.to_owned().iter().enumerate() {
-                    self.shadow_set(&fo.set, (Some(index), value))?;
-                    self.render_scope(&fo.scope, pipe)?;
-                }
-            }
-            Value::Object(ob) => {
-                for (key, value) in ob.to_owned().iter() {
-                    self.shadow_set(&fo.set, (Some(key), value))?;
-                    self.render_scope(&fo.scope, pipe)?;
-                }
-            }
+        let items: Vec<(Value, Value)> = match value.as_ref() {
+            Value::String(st) => st
+                .char_indices()
+                .map(|(index, char)| (json!(index), json!(char)))
+                .collect(),
+            Value::Array(ar) => ar
+                .iter()
+                .enumerate()
+                .map(|(index, value)| (json!(index), value.clone()))
+                .collect(),
+            Value::Object(ob) => ob
+                .iter()
+                .map(|(key, value)| (json!(key), value.clone()))
+                .collect(),
             incompatible => {
                 return Err(Error::build(INCOMPATIBLE_TYPES).with_help(format!(
                     "iterating on value `{}` is not supported",
                     incompatible
                 )))
             }
+        };
+        let length = items.len();
+
+        self.render_for_items(fo, items.into_iter(), length, pipe)
+    }
+
+    /// Render the [`Scope`] of a [`For`] once for each key and value pair.
+    ///
+    /// The `length` must be the number of pairs that `items` produces.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if rendering any [`Tree`] instance fails.
+    fn render_for_items<I>(
+        &mut self,
+        fo: &'source For,
+        items: I,
+        length: usize,
+        pipe: &mut Pipe,
+    ) -> Result<Flow, Error>
+    where
+        I: Iterator<Item = (Value, Value)>,
+    {
+        if length == 0 {
+            return match &fo.else_scope {
+                Some(else_scope) => self.render_scope(else_scope, pipe),
+                None => Ok(Flow::Proceed),
+            };
+        }
+        let parent = self.shadow.get_shadowed(LOOP).cloned();
+        let safe = self.is_safe_base(&fo.base);
+
+        self.shadow.push();
+        for (index, (key, value)) in items.enumerate() {
+            self.shadow_set(&fo.set, (Some(key), value), safe)?;
+            self.shadow.insert_must(
+                LOOP,
+                json!({
+                    "index": index + 1,
+                    "index0": index,
+                    "first": index == 0,
+                    "last": index + 1 == length,
+                    "length": length,
+                    "revindex": length - index,
+                    "parent": parent,
+                }),
+            );
+            if self.render_scope(&fo.scope, pipe)? == Flow::Break {
+                break;
+            }
         }
         self.shadow.pop();
 
-        Ok(())
+        Ok(Flow::Proceed)
     }
 
-    /// Return true if the entire [`IfBranch`] is truthy.
+    /// Return true if the [`Condition`] is true.
+    ///
+    /// The right side of `&&` and `||` is only evaluated when it can change
+    /// the result.
     ///
     /// # Errors
     ///
-    /// Returns an [`Error`] if a [`Value`] that the `IfBranch` depends on does
-    /// not exist in the [`Store`].
-    fn evaluate_branch(&self, branch: &IfBranch) -> Result<bool, Error> {
-        for leaf in branch {
-            let left = self.evaluate_base(&leaf.left)?;
-
-            match &leaf.right {
-                Some(base) => {
-                    let right = self.evaluate_base(&base)?;
-                    let operator = leaf
-                        .operator
-                        .expect("operator must exist when leaf.right exists");
-
-                    if !compare_values(&left, operator, &right)
-                        .map(|bool| if leaf.negate { !bool } else { bool })
-                        .map_err(|error| {
-                            error.with_pointer(
-                                self.template.get_source(),
-                                leaf.left.get_region().combine(base.get_region()),
-                            )
-                        })?
-                    {
-                        return Ok(false);
-                    }
-                }
-                None => {
-                    let result = match leaf.negate {
-                        true => !is_truthy(&left),
-                        false => is_truthy(&left),
-                    };
-
-                    if !result {
-                        return Ok(false);
-                    }
-                }
+    /// Returns an [`Error`] if a [`Value`] that the `Condition` depends on does
+    /// not exist in the [`Store`], or a [`Comparison`] fails.
+    fn evaluate_condition(&self, condition: &'source Condition) -> Result<bool, Error> {
+        match condition {
+            Condition::And(left, right) => {
+                Ok(self.evaluate_condition(left)? && self.evaluate_condition(right)?)
+            }
+            Condition::Or(left, right) => {
+                Ok(self.evaluate_condition(left)? || self.evaluate_condition(right)?)
             }
+            Condition::Not(condition) => Ok(!self.evaluate_condition(condition)?),
+            Condition::Truthy(base) => Ok(is_truthy(&*self.evaluate_base(base)?)),
+            Condition::Check(check) => self.evaluate_check(check),
+            Condition::Compare(comparison) => {
+                let left = self.evaluate_base(&comparison.left)?;
+                let right = self.evaluate_base(&comparison.right)?;
+
+                let coerce = self.engine.get_coerce();
+
+                compare_values(&left, comparison.operator, &right, coerce).map_err(|error| {
+                    error.with_pointer(self.template.get_source(), comparison.get_region())
+                })
+            }
+        }
+    }
+
+    /// Evaluate a [`Check`] by applying its [`Test`][`test::Test`] to its [`Base`].
+    ///
+    /// The `defined` and `undefined` tests are applied to the [`Store`] rather
+    /// than a [`Value`], so a missing variable is not an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the `Test` does not exist in the [`Engine`], or
+    /// the `Base` cannot be evaluated, or the `Test` returns an `Error`.
+    fn evaluate_check(&self, check: &'source Check) -> Result<bool, Error> {
+        let source = self.template.get_source();
+        let name = check.name.region.literal(source);
+        if RESERVED_TESTS.contains(&name) {
+            let defined = match &check.base {
+                Base::Variable(variable) => self.find_keys(&variable.path)?.is_ok(),
+                base => self.evaluate_base(base).map(|_| true)?,
+            };
+
+            return Ok(defined == (name == "defined"));
         }
 
-        Ok(true)
+        let test = self.engine.get_test(name).ok_or_else(|| {
+            Error::build(INVALID_TEST)
+                .with_pointer(source, check.name.region)
+                .with_help(format!(
+                    "template wants to use the `{name}` test, but a test with that name was not \
+                    found in this engine, did you add the test to the engine with `.add_test` or \
+                    `.add_test_must`?"
+                ))
+        })?;
+        let value = self.evaluate_base(&check.base)?;
+        let arguments = match &check.arguments {
+            Some(arguments) => self.evaluate_arguments(arguments)?,
+            None => HashMap::new(),
+        };
+
+        test.apply(&value, &arguments)
+            .map_err(|error| error.with_pointer(source, check.name.region))
     }
 
     /// Evaluate an [`Output`] to return a [`Value`].
@@ -311,6 +468,53 @@ impl<'source, 'store> Renderer<'source, 'store> {
         }
     }
 
+    /// Return true if the output of the [`Expression`] is safe to render without
+    /// escaping, because it ends with a [`Filter`][`crate::filter::Filter`] that
+    /// marks its output as safe, or its [`Base`] is safe.
+    ///
+    /// Only the final filter is consulted, so the output of any other filter is
+    /// escaped even when its input is safe.
+    fn is_safe(&self, expression: &Expression) -> bool {
+        match expression {
+            Expression::Call(call) => self
+                .engine
+                .get_filter(call.name.region.literal(self.template.get_source()))
+                .is_some_and(|filter| filter.is_safe()),
+            Expression::Base(base) => self.is_safe_base(base),
+        }
+    }
+
+    /// Return true if the [`Base`] is safe to render without escaping.
+    ///
+    /// A [`Literal`] is written by the author of the [`Template`], like raw text,
+    /// and the output of a [`Macro`] was already escaped as it was rendered.
+    /// A [`Variable`] is safe when the [`Store`] marks it as safe and it is not
+    /// shadowed, and a [`Fallback`] or [`Conditional`] is safe when each of its
+    /// branches is safe.
+    fn is_safe_base(&self, base: &Base) -> bool {
+        match base {
+            Base::Literal(_) | Base::Invoke(_) => true,
+            Base::Variable(variable) => match variable.path.first() {
+                Some(Key::Identifier(identifier)) => {
+                    let name = identifier.region.literal(self.template.get_source());
+                    self.shadow.is_safe(name)
+                }
+                _ => false,
+            },
+            Base::Fallback(fallback) => {
+                self.is_safe_base(&fallback.left) && self.is_safe_base(&fallback.right)
+            }
+            Base::Conditional(conditional) => {
+                self.is_safe_base(&conditional.then_base)
+                    && conditional
+                        .else_base
+                        .as_ref()
+                        .is_none_or(|else_base| self.is_safe_base(else_base))
+            }
+            _ => false,
+        }
+    }
+
     /// Evaluate a [`Base`] to return a [`Value`].
     ///
     /// # Errors
@@ -321,7 +525,370 @@ impl<'source, 'store> Renderer<'source, 'store> {
         match base {
             Base::Variable(variable) => self.evaluate_keys(&variable.path),
             Base::Literal(literal) => Ok(Cow::Borrowed(&literal.value)),
+            Base::Arithmetic(arithmetic) => {
+                let left = self.evaluate_base(&arithmetic.left)?;
+                let right = self.evaluate_base(&arithmetic.right)?;
+
+                calculate(&left, arithmetic.operator, &right)
+                    .map(Cow::Owned)
+                    .map_err(|error| {
+                        error.with_pointer(self.template.get_source(), arithmetic.region)
+                    })
+            }
+            Base::Range(range) => self
+                .evaluate_range(range)?
+                .into_value()
+                .map(Cow::Owned)
+                .map_err(|error| error.with_pointer(self.template.get_source(), range.region)),
+            Base::Fallback(fallback) => match self.evaluate_optional(&fallback.left)? {
+                Some(value) => Ok(value),
+                None => self.evaluate_base(&fallback.right),
+            },
+            Base::Invoke(invoke) => self.evaluate_invoke(invoke).map(Cow::Owned),
+            Base::Conditional(conditional) => {
+                if self.evaluate_condition(&conditional.condition)? {
+                    return self.evaluate_base(&conditional.then_base);
+                }
+
+                match &conditional.else_base {
+                    Some(else_base) => self.evaluate_base(else_base),
+                    None => Ok(Cow::Owned(Value::Null)),
+                }
+            }
+            Base::Array(array) => {
+                let values = array
+                    .values
+                    .iter()
+                    .map(|value| Ok(self.evaluate_base(value)?.into_owned()))
+                    .collect::<Result<_, Error>>()?;
+
+                Ok(Cow::Owned(Value::Array(values)))
+            }
+            Base::Object(object) => {
+                let pairs = object
+                    .pairs
+                    .iter()
+                    .map(|(key, value)| Ok((key.clone(), self.evaluate_base(value)?.into_owned())))
+                    .collect::<Result<_, Error>>()?;
+
+                Ok(Cow::Owned(Value::Object(pairs)))
+            }
+        }
+    }
+
+    /// Evaluate a [`Base`] to return a [`Value`], or [`None`] if the `Value` is
+    /// missing or null.
+    ///
+    /// A missing [`Variable`] does not cause an [`Error`], regardless of the
+    /// [`Undefined`] mode of the [`Engine`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if evaluating the `Base` fails for any other reason.
+    fn evaluate_optional(&self, base: &'source Base) -> Result<Option<Cow<'_, Value>>, Error> {
+        let value = match base {
+            Base::Variable(variable) => match self.find_keys(&variable.path)? {
+                Ok(value) => value,
+                Err(_) => return Ok(None),
+            },
+            Base::Fallback(fallback) => match self.evaluate_optional(&fallback.left)? {
+                Some(value) => value,
+                None => return self.evaluate_optional(&fallback.right),
+            },
+            base => self.evaluate_base(base)?,
+        };
+
+        Ok((!value.is_null()).then_some(value))
+    }
+
+    /// Evaluate an [`Invoke`] by rendering the named [`Macro`] to a string.
+    ///
+    /// A single name refers to a `Macro` defined in the [`Template`], or one
+    /// imported by name with `from`. A name within a namespace, such as
+    /// `forms.input`, refers to a `Macro` imported with `import ... as forms`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the `Macro` is not defined or imported, the
+    /// [`Import`] cannot be resolved, or rendering the `Macro` fails.
+    fn evaluate_invoke(&self, invoke: &'source Invoke) -> Result<Value, Error> {
+        let source = self.template.get_source();
+        let (namespace, identifier) = match &invoke.name.path[..] {
+            [Key::Identifier(identifier)] => (None, identifier),
+            [Key::Identifier(namespace), Key::Identifier(identifier)] => {
+                (Some(namespace.region.literal(source)), identifier)
+            }
+            _ => {
+                return Err(Error::build(INVALID_MACRO)
+                    .with_pointer(source, invoke.name.get_region())
+                    .with_help(
+                        "a macro is invoked by name, like `input()`, or by name within an \
+                        imported namespace, like `forms.input()`",
+                    ))
+            }
+        };
+        let name = identifier.region.literal(source);
+
+        if namespace.is_none() {
+            if let Some(definition) = self.template.get_macro(name) {
+                return self.render_macro(self.template, definition, invoke);
+            }
+        }
+        let import = self
+            .template
+            .get_imports()
+            .iter()
+            .find(|import| match (&import.binding, namespace) {
+                (Binding::Namespace(alias), Some(namespace)) => {
+                    alias.region.literal(source) == namespace
+                }
+                (Binding::Macros(macros), None) => macros
+                    .iter()
+                    .any(|imported| imported.region.literal(source) == name),
+                _ => false,
+            })
+            .ok_or_else(|| match namespace {
+                Some(namespace) => Error::build(INVALID_MACRO)
+                    .with_pointer(source, invoke.name.get_region())
+                    .with_help(format!(
+                        "namespace `{namespace}` is not imported in this template, import it \
+                        with `import \"...\" as {namespace}` at the top level of the template"
+                    )),
+                None => Error::build(INVALID_MACRO)
+                    .with_pointer(source, invoke.name.get_region())
+                    .with_help(format!(
+                        "macro `{name}` is not defined in this template, define it with \
+                        `macro {name}()` at the top level of the template"
+                    )),
+            })?;
+
+        let template = self.resolve_import(import)?;
+        let definition = template.get_macro(name).ok_or_else(|| {
+            Error::build(INVALID_MACRO)
+                .with_pointer(source, identifier.region)
+                .with_help(format!(
+                    "macro `{name}` is not defined in template `{}`",
+                    self.evaluate_name(&import.name)
+                ))
+        })?;
+
+        self.render_macro(&template, definition, invoke)
+    }
+
+    /// Resolve the [`Template`] named by an [`Import`] from the [`Engine`].
+    ///
+    /// The first time a `Template` is imported during a render, its imports are
+    /// resolved in turn, so an import cycle is found even when the macros involved
+    /// are never invoked. Later imports of the same `Template` reuse the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the `Template` is not found, the `Template` imports
+    /// the `Template` being rendered, directly or through others, or a [`Macro`]
+    /// imported by name is not defined in the `Template`.
+    fn resolve_import(&self, import: &Import) -> Result<Arc<Template>, Error> {
+        let source = self.template.get_source();
+        let name = self.evaluate_name(&import.name);
+        let importers = || {
+            self.importers
+                .iter()
+                .map(String::as_str)
+                .chain(self.template.get_name())
+        };
+
+        if importers().any(|importer| importer == name) {
+            return Err(Error::build(INVALID_IMPORT)
+                .with_pointer(source, import.region)
+                .with_help(format!(
+                    "template `{name}` cannot be imported here, because it imports itself \
+                    through `{} -> {name}`",
+                    importers().collect::<Vec<_>>().join(" -> ")
+                )));
+        }
+        let imported = self.imported.borrow().get(name).cloned();
+        let template = match imported {
+            Some(template) => template,
+            None => {
+                let template = self
+                    .engine
+                    .resolve_template(name)?
+                    .ok_or_else(|| error_missing_template(name))?;
+
+                let mut renderer = Renderer::new(self.engine, &template, self.shadow.store)
+                    .with_imported(self.imported.clone());
+                renderer.importers = importers().map(str::to_string).collect();
+                for nested in template.get_imports() {
+                    renderer
+                        .resolve_import(nested)
+                        .map_err(|error| name_error(error, &template))?;
+                }
+                self.imported
+                    .borrow_mut()
+                    .insert(name.to_string(), template.clone());
+
+                template
+            }
+        };
+
+        if let Binding::Macros(macros) = &import.binding {
+            for imported in macros {
+                let literal = imported.region.literal(source);
+                if template.get_macro(literal).is_none() {
+                    return Err(Error::build(INVALID_IMPORT)
+                        .with_pointer(source, imported.region)
+                        .with_help(format!(
+                            "macro `{literal}` is not defined in template `{name}`"
+                        )));
+                }
+            }
+        }
+
+        Ok(template)
+    }
+
+    /// Render a [`Macro`] defined in the given [`Template`], with the arguments
+    /// of the [`Invoke`], and return the output as a string.
+    ///
+    /// The `Macro` is rendered with a new [`Renderer`], so values assigned by
+    /// the caller are not visible within the `Macro`, and values assigned within
+    /// the `Macro` do not leak out of it. A [`Parameter`] without an argument is
+    /// assigned its default value, which may refer to earlier parameters.
+    ///
+    /// Expressions within the `Macro` are escaped according to the `Renderer`
+    /// that invokes it, rather than the `Template` that defines it, because the
+    /// output is not escaped again by the caller.
+    ///
+    /// Errors from the arguments belong to the caller, while errors from the
+    /// default values and body of the `Macro` are given the name of its `Template`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] that points at the `Invoke` if the arguments do not
+    /// match the parameters, or the `Macro` is nested too deeply within other
+    /// calls, or rendering the `Macro` fails.
+    fn render_macro<'definition>(
+        &self,
+        template: &'definition Template,
+        definition: &'definition Macro,
+        invoke: &'source Invoke,
+    ) -> Result<Value, Error> {
+        let source = self.template.get_source();
+        let name = definition.name.region.literal(template.get_source());
+        let parameters = &definition.parameters;
+        if self.depth >= MAX_DEPTH {
+            return Err(Error::build(INVALID_MACRO)
+                .with_pointer(source, invoke.region)
+                .with_help(format!(
+                    "macro `{name}` is nested more than {MAX_DEPTH} calls deep, \
+                    check for macros that call each other without end"
+                )));
+        }
+
+        let mut values: Vec<Option<(Value, bool)>> = vec![None; parameters.len()];
+        let mut position = 0;
+        for argument in &invoke.arguments {
+            let index = match argument.name {
+                Some(region) => {
+                    let literal = region.literal(source);
+                    parameters
+                        .iter()
+                        .position(|parameter| {
+                            parameter.name.region.literal(template.get_source()) == literal
+                        })
+                        .ok_or_else(|| {
+                            Error::build(INVALID_ARGUMENT)
+                                .with_pointer(source, region)
+                                .with_help(format!(
+                                    "macro `{name}` does not have a parameter named `{literal}`"
+                                ))
+                        })?
+                }
+                None => {
+                    position += 1;
+                    if position > parameters.len() {
+                        return Err(Error::build(INVALID_ARGUMENT)
+                            .with_pointer(source, argument.get_region())
+                            .with_help(format!(
+                                "macro `{name}` takes {} argument(s), but more were given",
+                                parameters.len()
+                            )));
+                    }
+
+                    position - 1
+                }
+            };
+            if values[index].is_some() {
+                return Err(Error::build(INVALID_ARGUMENT)
+                    .with_pointer(source, argument.get_region())
+                    .with_help(format!(
+                        "argument `{}` of macro `{name}` is given more than once",
+                        parameters[index].name.region.literal(template.get_source())
+                    )));
+            }
+
+            let value = self.evaluate_base(&argument.value)?.into_owned();
+            values[index] = Some((value, self.is_safe_base(&argument.value)));
+        }
+
+        let mut renderer = Renderer::new(self.engine, template, self.shadow.store)
+            .with_imported(self.imported.clone());
+        renderer.depth = self.depth + 1;
+        // The output is trusted by the caller, so escape it the way the caller would.
+        renderer.escape = self.escape;
+        for (parameter, value) in parameters.iter().zip(values) {
+            let parameter_name = parameter.name.region.literal(template.get_source());
+            let (value, safe) = match (value, &parameter.default) {
+                (Some(value), _) => value,
+                (None, Some(default)) => (
+                    renderer
+                        .evaluate_base(default)
+                        .map_err(|error| name_error(error, template))?
+                        .into_owned(),
+                    renderer.is_safe_base(default),
+                ),
+                (None, None) => {
+                    return Err(Error::build(INVALID_ARGUMENT)
+                        .with_pointer(source, invoke.region)
+                        .with_help(format!(
+                            "macro `{name}` requires argument `{parameter_name}`, pass it by \
+                            position or as `{parameter_name}: value`"
+                        )))
+                }
+            };
+            renderer
+                .shadow
+                .insert_safe_must(parameter_name, value, safe);
         }
+
+        let mut buffer = String::new();
+        renderer
+            .render_scope(&definition.scope, &mut Pipe::new(&mut buffer))
+            .map_err(|error| name_error(error, template))?;
+
+        Ok(Value::String(buffer))
+    }
+
+    /// Evaluate a [`Range`] to return a [`Sequence`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if a bound or step of the `Range` cannot be evaluated,
+    /// is not an integer, or the step is zero.
+    fn evaluate_range(&self, range: &'source Range) -> Result<Sequence, Error> {
+        let integer = |base: &'source Base, name: &str| {
+            expect_integer(&*self.evaluate_base(base)?, name)
+                .map_err(|error| error.with_pointer(self.template.get_source(), base.get_region()))
+        };
+
+        let start = integer(&range.start, "start")?;
+        let end = integer(&range.end, "end")?;
+        let step = match &range.step {
+            Some(step) => integer(step, "step")?,
+            None => 1,
+        };
+
+        Sequence::new(start, end, step, range.inclusive)
+            .map_err(|error| error.with_pointer(self.template.get_source(), range.region))
     }
 
     /// Evaluate a [`Call`] to return a [`Value`].
@@ -386,50 +953,125 @@ impl<'source, 'store> Renderer<'source, 'store> {
         region.literal(self.template.get_source())
     }
 
-    /// Evaluate a set of [`Identifier`] instances to return a [`Value`] from the [`Store`].
+    /// Evaluate a [`Base`] that names a [`Template`] or [`Block`] to return a `&str`.
+    ///
+    /// A string literal is evaluated to its contents, so `"layouts/base.html"` and
+    /// `layouts` name templates without the surrounding quotes. Any other `Base` is
+    /// evaluated to the literal value of its [`Region`] within the source text.
+    fn evaluate_name<'name>(&self, base: &'name Base) -> &'name str
+    where
+        'source: 'name,
+    {
+        match base {
+            Base::Literal(Literal {
+                value: Value::String(string),
+                ..
+            }) => string,
+            _ => base.get_region().literal(self.template.get_source()),
+        }
+    }
+
+    /// Evaluate a set of [`Key`] instances to return a [`Value`] from the [`Store`].
+    ///
+    /// When a `Key` is not found, such as an index beyond the end of an array,
+    /// the [`Undefined`] mode of the [`Engine`] determines the result.
     ///
     /// # Errors
     ///
-    /// Returns an [`Error`] if a `Value` that an `Identifier` depends on does not exist in
-    /// the `Store`.
-    fn evaluate_keys(&self, keys: &Vec<Identifier>) -> Result<Cow<Value>, Error> {
-        let first_region = keys
-            .first()
-            .expect("key vector should always have at least one key")
-            .region;
+    /// Returns an [`Error`] if a `Key` is not found and the mode is
+    /// [`Undefined::Strict`], the first `Key` is not found and the mode is
+    /// [`Undefined::Shallow`], or an [`Index`] does not evaluate to an integer
+    /// or string.
+    fn evaluate_keys(&self, keys: &'source [Key]) -> Result<Cow<Value>, Error> {
+        let position = match self.find_keys(keys)? {
+            Ok(value) => return Ok(value),
+            Err(position) => position,
+        };
+        let source = self.template.get_source();
+        let missing = keys[position].get_region();
 
-        let first_value = first_region.literal(self.template.get_source());
-        let store_value = self.shadow.get(first_value);
+        match self.engine.get_undefined() {
+            Undefined::Strict | Undefined::Shallow if position == 0 => {
+                let name = missing.literal(source);
 
-        let mut value: Cow<Value> = if store_value.is_some() {
-            Cow::Borrowed(store_value.unwrap())
-        } else {
-            return Err(Error::build("missing store value")
-                .with_pointer(self.template.get_source(), first_region)
-                .with_help(format!(
-                    "unable to find `{first_value}` in store, \
-                    ensure it exists or try wrapping with an `if` block",
-                )));
+                Err(Error::build(MISSING_VALUE)
+                    .with_pointer(source, missing)
+                    .with_help(format!(
+                        "unable to find `{name}` in store, \
+                        ensure it exists or try wrapping with an `if` block",
+                    )))
+            }
+            Undefined::Strict => {
+                let parent = keys[0]
+                    .get_region()
+                    .combine(keys[position - 1].get_region());
+
+                Err(Error::build(MISSING_VALUE)
+                    .with_pointer(source, missing)
+                    .with_help(format!(
+                        "unable to find `{}` in `{}`, \
+                        ensure it exists or try wrapping with an `if` block",
+                        missing.literal(source).trim_start_matches('.'),
+                        parent.literal(source),
+                    )))
+            }
+            Undefined::Shallow | Undefined::Lenient => Ok(Cow::Owned(Value::Null)),
+            Undefined::Debug => {
+                let path = keys[0].get_region().combine(missing);
+                self.undefined.set(self.undefined.get().or(Some(path)));
+
+                Ok(Cow::Owned(Value::Null))
+            }
+        }
+    }
+
+    /// Follow a set of [`Key`] instances through the [`Store`] to return a [`Value`].
+    ///
+    /// The inner result is the position of the first `Key` that was not found,
+    /// if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if an [`Index`] does not evaluate to an integer or string.
+    fn find_keys(&self, keys: &'source [Key]) -> Result<Result<Cow<'_, Value>, usize>, Error> {
+        let first_region = match keys.first() {
+            Some(Key::Identifier(identifier)) => identifier.region,
+            _ => unreachable!("key vector should always begin with an identifier"),
         };
 
-        for key in keys.iter().skip(1) {
-            match value.as_object() {
-                Some(object) => {
-                    let key_region = key.region;
-                    let key_name = key_region.literal(self.template.get_source());
-                    let next_object = object.get(key_name);
+        let first_value = first_region.literal(self.template.get_source());
+        let mut value: Cow<Value> = match self.shadow.get(first_value) {
+            Some(value) => Cow::Borrowed(value),
+            None => return Ok(Err(0)),
+        };
 
-                    value = if next_object.is_some() {
-                        Cow::Owned(next_object.unwrap().clone())
-                    } else {
-                        Cow::Owned(Value::Null)
-                    };
+        for (position, key) in keys.iter().enumerate().skip(1) {
+            let next_value = match key {
+                Key::Identifier(identifier) => {
+                    value.get(identifier.region.literal(self.template.get_source()))
                 }
-                None => return Ok(Cow::Owned(Value::Null)),
-            }
+                Key::Index(index) => match self.evaluate_base(&index.base)?.as_ref() {
+                    Value::String(string) => value.get(string),
+                    Value::Number(number) if number.is_i64() => {
+                        get_index(&value, number.as_i64().unwrap())
+                    }
+                    invalid => {
+                        return Err(Error::build(INVALID_INDEX)
+                            .with_pointer(self.template.get_source(), index.region)
+                            .with_help(format!(
+                                "expected an integer or string to index with, found `{invalid}`"
+                            )))
+                    }
+                },
+            };
+
+            value = match next_value {
+                Some(next_value) => Cow::Owned(next_value.clone()),
+                None => return Ok(Err(position)),
+            };
         }
 
-        Ok(value)
+        Ok(Ok(value))
     }
 
     /// Evaluate an [`Arguments`] to return a [`HashMap`] that contains the same values.
@@ -471,9 +1113,11 @@ impl<'source, 'store> Renderer<'source, 'store> {
     /// exist in the [`Store`].
     fn evaluate_let(&mut self, le: &Let) -> Result<(), Error> {
         let value = self.evaluate_expression(&le.right)?;
+        let safe = self.is_safe(&le.right);
         self.shadow_set(
             &Set::Single(le.left.clone()),
             (None::<Value>, value.into_owned()),
+            safe,
         )?;
 
         Ok(())
@@ -488,6 +1132,14 @@ impl<'source, 'store> Renderer<'source, 'store> {
         self
     }
 
+    /// Share the imported templates of another [`Renderer`], so each is only
+    /// resolved once per render.
+    fn with_imported(mut self, imported: ImportMap) -> Self {
+        self.imported = imported;
+
+        self
+    }
+
     /// Clone all of the [`Block`] instances in the given [`Scope`] into
     /// the Renderer.
     fn collect_blocks(&mut self, scope: &'source Scope) {
@@ -496,7 +1148,7 @@ impl<'source, 'store> Renderer<'source, 'store> {
         while let Some(next) = iterator.next() {
             match next {
                 Tree::Block(block) => {
-                    let name = block.name.get_region().literal(self.template.get_source());
+                    let name = self.evaluate_name(&block.name);
                     self.blocks.insert(
                         name.to_string(),
                         Named {
@@ -510,7 +1162,8 @@ impl<'source, 'store> Renderer<'source, 'store> {
         }
     }
 
-    /// Assign the given data to the [`Set`].
+    /// Assign the given data to the [`Set`], marking it as safe to render
+    /// without escaping when `safe` is true.
     ///
     /// # Errors
     ///
@@ -520,7 +1173,7 @@ impl<'source, 'store> Renderer<'source, 'store> {
     ///
     /// Panics when a `Set` of type `Pair` is received, but the .0 property in the
     /// "pair" parameter is None.
-    fn shadow_set<N, T>(&mut self, set: &Set, data: (Option<N>, T)) -> Result<(), Error>
+    fn shadow_set<N, T>(&mut self, set: &Set, data: (Option<N>, T), safe: bool) -> Result<(), Error>
     where
         N: Serialize + Display,
         T: Serialize + Display,
@@ -529,13 +1182,13 @@ impl<'source, 'store> Renderer<'source, 'store> {
         match set {
             Set::Single(si) => {
                 let key = si.region.literal(&source);
-                self.shadow.insert_must(key, data.1)
+                self.shadow.insert_safe_must(key, data.1, safe)
             }
             Set::Pair(pa) => {
                 let key = pa.key.region.literal(&source);
                 let value = pa.value.region.literal(&source);
-                self.shadow.insert_must(key, data.0.unwrap());
-                self.shadow.insert_must(value, data.1);
+                self.shadow.insert_safe_must(key, data.0.unwrap(), safe);
+                self.shadow.insert_safe_must(value, data.1, safe);
             }
         }
 
@@ -543,24 +1196,66 @@ impl<'source, 'store> Renderer<'source, 'store> {
     }
 }
 
+/// Return the [`Value`] at the given index of an array, or [`None`] if the
+/// `Value` is not an array or the index is out of range.
+///
+/// A negative index counts back from the end of the array, so `-1` is the
+/// last element.
+fn get_index(value: &Value, index: i64) -> Option<&Value> {
+    let array = value.as_array()?;
+    let index = match index {
+        index if index < 0 => array.len().checked_sub(index.unsigned_abs() as usize)?,
+        index => index as usize,
+    };
+
+    array.get(index)
+}
+
+/// Return the [`Error`] with the name of the given [`Template`].
+///
+/// The `Error` might come from another `Template`, so the name is not changed
+/// if it already has one.
+fn name_error(error: Error, template: &Template) -> Error {
+    match (error.get_name(), template.get_name()) {
+        (None, Some(name)) => error.with_name(name),
+        _ => error,
+    }
+}
+
 /// Return an [`Error`] describing a missing template.
-fn error_missing_template(name: &str) -> Error {
+pub fn error_missing_template(name: &str) -> Error {
     Error::build("missing template").with_help(format!(
-        "template `{}` not found in engine, add it with `.add_template`",
+        "template `{}` not found in engine, add it with `.add_template` \
+        or provide a loader with `.set_loader`",
         name
     ))
 }
 
 /// Return an [`Error`] explaining that the write operation failed.
 ///
-/// This is likely caused by a failure during a `write!` macro operation.
-fn error_write() -> Error {
+/// This is caused by the writer given to the [`Pipe`] returning an error
+/// during a `write!` macro operation, or failing to flush.
+pub(crate) fn error_write() -> Error {
     Error::build("write failure")
-        .with_help("failed to write result of render, are you low on memory?")
+        .with_help("failed to write result of render, the writer returned an error")
 }
 
 type BlockMap<'source> = HashMap<String, Named<'source>>;
 
+/// Imported [`Template`] instances by name, shared by the nested renderers of a render.
+type ImportMap = Rc<RefCell<HashMap<String, Arc<Template>>>>;
+
+/// Describes how a [`For`] should proceed after rendering a [`Scope`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flow {
+    /// Continue rendering normally.
+    Proceed,
+    /// Stop the enclosing [`For`].
+    Break,
+    /// Skip to the next iteration of the enclosing [`For`].
+    Continue,
+}
+
 /// A wrapper for [`Block`] that includes a reference to the [`Template`]
 /// that the `Block` was found in.
 struct Named<'source> {
@@ -572,15 +1267,23 @@ struct Named<'source> {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::SystemTime,
+    };
 
     use crate::{
         compile::tree::{Argument, Arguments, Base, Literal},
         filter::Error,
+        load::Loader,
         Engine, Store, Template,
     };
 
-    use super::Renderer;
+    use super::{Escape, Renderer, Undefined};
 
     use serde_json::{json, Value};
 
@@ -605,6 +1308,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_render_output_group() {
+        let (template, engine) = get_template_with_engine(
+            "(( (x ?? 1) * 2 )) (( (a if c else b) + 1 )) (( ((a + b) * 2) ))",
+        );
+        let store = Store::new()
+            .with_must("a", 10)
+            .with_must("b", 20)
+            .with_must("c", false);
+
+        assert_eq!(engine.render(&template, &store).unwrap(), "2 21 60");
+    }
+
+    #[test]
+    fn test_render_output_keyword_names() {
+        let (template, engine) = get_template_with_engine(
+            "(( raw )) (( import.count )) (( is )) (( macro )) \
+            (( break if continue is defined ))",
+        );
+        let store = Store::new()
+            .with_must("raw", "a")
+            .with_must("import", json!({"count": 2}))
+            .with_must("is", "b")
+            .with_must("macro", "c")
+            .with_must("break", "d")
+            .with_must("continue", true);
+
+        assert_eq!(engine.render(&template, &store).unwrap(), "a 2 b c d");
+    }
+
+    #[test]
+    fn test_render_output_literal_names() {
+        let (template, engine) = get_template_with_engine(
+            "(( null ?? \"-\" )) (( flags[\"null\"] )) (( flags[\"true\"] ))",
+        );
+        let store = Store::new()
+            .with_must("null", "a")
+            .with_must("flags", json!({"null": "b", "true": "c"}));
+
+        assert_eq!(engine.render(&template, &store).unwrap(), "- b c");
+        assert!(engine.compile("(* let null = 1 *)").is_err());
+        assert!(engine.compile("(* for null in items *)(* end *)").is_err());
+    }
+
     #[test]
     fn test_render_output_whitespace() {
         let (template, engine) = get_template_with_engine("hello there, ((- name -)) !");
@@ -702,9 +1449,637 @@ mod tests {
     }
 
     #[test]
-    fn test_let_global_scope_if() {
-        let (template, mut engine) = get_template_with_engine(
-            "(* if is_admin *)\
+    fn test_render_for_loop() {
+        let (template, engine) = get_template_with_engine(
+            "(* for value in data *)\
+                (( loop.index ))/(( loop.length )) \
+                (( loop.index0 )) (( loop.revindex )) \
+                (* if loop.first *)first (* end *)\
+                (* if loop.last *)last(* end *)|\
+            (* end *)",
+        );
+        let store = Store::new().with_must("data", json!(["a", "b", "c"]));
+
+        assert_eq!(
+            engine.render(&template, &store).unwrap(),
+            "1/3 0 3 first |2/3 1 2 |3/3 2 1 last|"
+        );
+    }
+
+    #[test]
+    fn test_render_for_loop_parent() {
+        let (template, engine) = get_template_with_engine(
+            "(* for outer in first *)\
+                (* for inner in second *)\
+                    (( loop.parent.index )).(( loop.index )) \
+                (* end *)\
+                (( loop.index ))(( loop.parent ))|\
+            (* end *)",
+        );
+        let store = Store::new()
+            .with_must("first", "ab")
+            .with_must("second", "cd");
+
+        assert_eq!(
+            engine.render(&template, &store).unwrap(),
+            "1.1 1.2 1|2.1 2.2 2|"
+        );
+    }
+
+    #[test]
+    fn test_render_for_else() {
+        let (template, engine) = get_template_with_engine(
+            "(* for value in data *)\
+                (( value ))\
+            (* else *)\
+                empty\
+            (* end *)",
+        );
+
+        for (data, expect) in [
+            (json!(["a", "b"]), "ab"),
+            (json!([]), "empty"),
+            (json!({}), "empty"),
+            (json!(""), "empty"),
+        ] {
+            let store = Store::new().with_must("data", data);
+
+            assert_eq!(engine.render(&template, &store).unwrap(), expect);
+        }
+    }
+
+    #[test]
+    fn test_render_for_break_continue() {
+        let (template, engine) = get_template_with_engine(
+            "(* for value in data *)\
+                (* if value == 2 *)(* continue *)(* end *)\
+                (* for inner in data *)\
+                    (* if inner > 1 *)(* break *)(* end *)\
+                    (( inner ))\
+                (* end *)\
+                (* if value == 4 *)(* break *)(* end *)\
+                (( value )) \
+            (* end *)",
+        );
+        let store = Store::new().with_must("data", json!([1, 2, 3, 4, 5]));
+
+        assert_eq!(engine.render(&template, &store).unwrap(), "11 13 1");
+    }
+
+    #[test]
+    fn test_render_collection() {
+        let mut engine = Engine::default();
+        let card = engine
+            .compile("(( title )):(* for tag in tags *) (( tag ))(* end *)")
+            .unwrap();
+        engine.add_template("card", card);
+        let template = engine
+            .compile(
+                "(* include card title: page.title, tags: [\"new\", page.tag] *)|\
+                (* for key, value in { first: page.title, second: [1, 2] } *)\
+                (( key ))=(( value )) \
+                (* end *)",
+            )
+            .unwrap();
+        let store = Store::new().with_must("page", json!({"title": "shoes", "tag": "sale"}));
+
+        assert_eq!(
+            engine.render(&template, &store).unwrap(),
+            "shoes: new sale|first=shoes second=[1, 2] "
+        );
+    }
+
+    #[test]
+    fn test_render_index() {
+        let (template, mut engine) = get_template_with_engine(
+            "(( items.0 ))|(( items[1].name ))|(( items[-1].name ))|(( items[index].name ))|\
+            (( map[key] ))|(( map[\"b\"] ))|(( items[5] ))|(( items[-5] ))|(( map.a[0] ))",
+        );
+        let store = Store::new()
+            .with_must("items", json!(["a", {"name": "b"}, {"name": "c"}]))
+            .with_must("index", 2)
+            .with_must("map", json!({"a": [1], "b": 2}))
+            .with_must("key", "a");
+        engine.set_undefined(Undefined::Lenient);
+
+        assert_eq!(
+            engine.render(&template, &store).unwrap(),
+            "a|b|c|c|[1]|2|||1"
+        );
+    }
+
+    #[test]
+    fn test_render_index_invalid() {
+        let (template, engine) = get_template_with_engine("(( items[flag] ))");
+        let store = Store::new()
+            .with_must("items", json!([1]))
+            .with_must("flag", true);
+
+        assert!(engine.render(&template, &store).is_err());
+    }
+
+    #[test]
+    fn test_render_check() {
+        let engine = Engine::default().with_test_must(
+            "short",
+            |value: &Value, _: &HashMap<String, Value>| {
+                Ok(value.as_str().is_some_and(|string| string.len() < 5))
+            },
+        );
+        let template = engine
+            .compile(
+                "(* if user is defined && user.nmae is undefined *)a(* end *)\
+                (* if ghost is not defined *)b(* end *)\
+                (* if user.name is not short *)c(* end *)\
+                (( \"d\" if (user.name is short || user.tags is empty) ))",
+            )
+            .unwrap();
+        let store = Store::new().with_must("user", json!({"name": "taylor", "tags": []}));
+
+        assert_eq!(engine.render(&template, &store).unwrap(), "abcd");
+    }
+
+    #[test]
+    fn test_render_check_missing() {
+        let engine = Engine::default();
+        let source = "(* if name is shouting *)(* end *)";
+        let template = engine.compile(source).unwrap();
+        let result = engine.render(&template, &Store::new().with_must("name", "taylor"));
+
+        assert_eq!(
+            result,
+            Err(Error::build("invalid test")
+                .with_pointer(source, 14..22)
+                .with_help(
+                    "template wants to use the `shouting` test, but a test with that name was not \
+                    found in this engine, did you add the test to the engine with `.add_test` or \
+                    `.add_test_must`?"
+                ))
+        );
+    }
+
+    #[test]
+    fn test_render_macro() {
+        let mut engine = Engine::default();
+        engine
+            .insert_template_must(
+                "button.html",
+                "(* macro button(label, kind=\"primary\", class=kind + \"-button\") -*)\n\
+                    (* let inner = \"leak\" -*)\n\
+                    <button class=\"(( class ))\">(( label ))(( outer ?? \"\" ))</button>\
+                (*- end -*)\n\
+                (* let outer = \"!\" -*)\n\
+                (( button(\"<Save>\") ))|(( button(kind: \"link\", label: title) ))|(( inner ?? \"none\" ))|\
+                (( button((title)) ))",
+            )
+            .unwrap();
+        let template = engine.get_template("button.html").unwrap();
+        let store = Store::new().with_must("title", "Back");
+
+        assert_eq!(
+            engine.render(template, &store).unwrap(),
+            "<button class=\"primary-button\"><Save></button>|\
+            <button class=\"link-button\">Back</button>|none|\
+            <button class=\"primary-button\">Back</button>"
+        );
+    }
+
+    #[test]
+    fn test_render_macro_arguments() {
+        let engine = Engine::default();
+        let definition =
+            "(* macro greet(name, greeting=\"hello\") *)(( greeting )), (( name ))(* end *)";
+
+        for (call, region, help) in [
+            (
+                "greet()",
+                0..7,
+                "macro `greet` requires argument `name`, pass it by position or as `name: value`",
+            ),
+            (
+                "greet(1, 2, 3)",
+                12..13,
+                "macro `greet` takes 2 argument(s), but more were given",
+            ),
+            (
+                "greet(1, nmae: 2)",
+                9..13,
+                "macro `greet` does not have a parameter named `nmae`",
+            ),
+            (
+                "greet(1, name: 2)",
+                9..16,
+                "argument `name` of macro `greet` is given more than once",
+            ),
+        ] {
+            let source = format!("{definition}(( {call} ))");
+            let offset = definition.len() + 3;
+            let template = engine.compile(&source).unwrap();
+
+            assert_eq!(
+                engine.render(&template, &Store::new()),
+                Err(Error::build("invalid argument")
+                    .with_pointer(&source, region.start + offset..region.end + offset)
+                    .with_help(help))
+            );
+        }
+
+        let template = engine.compile("(( greet(\"taylor\") ))").unwrap();
+        assert!(engine.render(&template, &Store::new()).is_err());
+    }
+
+    #[test]
+    fn test_render_macro_recursion() {
+        let engine = Engine::default();
+
+        for (source, name, region) in [
+            ("(* macro m() *)(( m() ))(* end *)(( m() ))", "m", 18..21),
+            (
+                "(* macro ping() *)(( pong() ))(* end *)(* macro pong() *)(( ping() ))(* end *)\
+                (( ping() ))",
+                "ping",
+                60..66,
+            ),
+        ] {
+            let template = engine.compile(source).unwrap();
+
+            assert_eq!(
+                engine.render(&template, &Store::new()),
+                Err(Error::build("invalid macro")
+                    .with_pointer(source, region)
+                    .with_help(format!(
+                        "macro `{name}` is nested more than 32 calls deep, \
+                        check for macros that call each other without end"
+                    )))
+            );
+        }
+
+        let template = engine
+            .compile(
+                "(* macro count(n) *)(* if n > 0 *)(( n ))(( count(n - 1) ))(* end *)(* end *)\
+                (( count(5) ))",
+            )
+            .unwrap();
+        assert_eq!(engine.render(&template, &Store::new()).unwrap(), "54321");
+    }
+
+    #[test]
+    fn test_render_import() {
+        let mut engine = Engine::default();
+        engine
+            .insert_template_must(
+                "forms.html",
+                "(* macro input(name, kind=\"text\") *)<input type=\"(( kind ))\" name=\"(( name ))\">(* end *)\
+                (* macro label(text) *)<label>(( text ))</label>(* end *)\
+                (* macro field(name) *)(( label(name) ))(( input(name) ))(* end *)",
+            )
+            .unwrap();
+        engine
+            .insert_template_must(
+                "layout.html",
+                "(* from \"forms.html\" import label *)\
+                (* macro heading(text) *)<h1>(( label(text) ))</h1>(* end *)",
+            )
+            .unwrap();
+        let template = engine
+            .compile(
+                "(* import \"forms.html\" as forms *)(* from \"layout.html\" import heading *)\
+                (( heading(\"Sign in\") ))(( forms.field(\"user\") ))(( forms.input(\"pass\", kind: \"password\") ))",
+            )
+            .unwrap();
+
+        assert_eq!(
+            engine.render(&template, &Store::new()).unwrap(),
+            "<h1><label>Sign in</label></h1><label>user</label><input type=\"text\" name=\"user\">\
+            <input type=\"password\" name=\"pass\">"
+        );
+    }
+
+    #[test]
+    fn test_render_import_invalid() {
+        let mut engine = Engine::default();
+        engine
+            .insert_template_must("forms.html", "(* macro input() *)(* end *)")
+            .unwrap();
+
+        for (source, title, region, help) in [
+            (
+                "(* import \"forms.html\" as forms *)(( forms.select() ))",
+                "invalid macro",
+                44..50,
+                "macro `select` is not defined in template `forms.html`",
+            ),
+            (
+                "(* import \"forms.html\" as forms *)(( form.input() ))",
+                "invalid macro",
+                38..48,
+                "namespace `form` is not imported in this template, import it with \
+                `import \"...\" as form` at the top level of the template",
+            ),
+            (
+                "(* from \"forms.html\" import input, select *)(( select() ))",
+                "invalid import",
+                36..42,
+                "macro `select` is not defined in template `forms.html`",
+            ),
+        ] {
+            let template = engine.compile(source).unwrap();
+
+            assert_eq!(
+                engine.render(&template, &Store::new()),
+                Err(Error::build(title)
+                    .with_pointer(source, region)
+                    .with_help(help))
+            );
+        }
+
+        let template = engine
+            .compile("(* import \"ghost.html\" as ghost *)(( ghost.input() ))")
+            .unwrap();
+        assert!(engine.render(&template, &Store::new()).is_err());
+    }
+
+    #[test]
+    fn test_render_import_error_name() {
+        let mut engine = Engine::default();
+        engine
+            .insert_template_must(
+                "forms.html",
+                "(* macro input(name) *)(( name ))(* end *)(* macro broken() *)(( ghost ))(* end *)",
+            )
+            .unwrap();
+        let page = "(* import \"forms.html\" as forms *)(( forms.input(bogus: 1) ))";
+        engine.insert_template_must("page.html", page).unwrap();
+
+        assert_eq!(
+            engine.render(engine.get_template("page.html").unwrap(), &Store::new()),
+            Err(Error::build("invalid argument")
+                .with_pointer(page, 50..55)
+                .with_help("macro `input` does not have a parameter named `bogus`")
+                .with_name("page.html"))
+        );
+
+        engine
+            .insert_template_must(
+                "other.html",
+                "(* import \"forms.html\" as forms *)(( forms.broken() ))",
+            )
+            .unwrap();
+        let error = engine
+            .render(engine.get_template("other.html").unwrap(), &Store::new())
+            .unwrap_err();
+        assert_eq!(error.get_name(), Some("forms.html"));
+    }
+
+    #[test]
+    fn test_render_import_once() {
+        struct Counting(Arc<AtomicUsize>);
+
+        impl Loader for Counting {
+            fn load(&self, name: &str) -> Result<Option<String>, Error> {
+                Ok(match name {
+                    "forms.html" => Some("(* macro input(n) *)(( n ))(* end *)".to_string()),
+                    _ => None,
+                })
+            }
+
+            fn modified(&self, _: &str) -> Option<SystemTime> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                None
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let engine = Engine::default()
+            .with_loader(Counting(count.clone()))
+            .with_reload(true);
+        let template = engine
+            .compile(
+                "(* import \"forms.html\" as forms *)\
+                (* macro row(n) *)(( forms.input(n) ))(* end *)\
+                (* for n in 0..100 *)(( forms.input(n) ))(( row(n) ))(* end *)",
+            )
+            .unwrap();
+
+        assert!(engine.render(&template, &Store::new()).is_ok());
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_render_import_cycle() {
+        let mut engine = Engine::default();
+        let first = "(* import \"second.html\" as second *)(* macro a() *)a(* end *)";
+        engine.insert_template_must("first.html", first).unwrap();
+        engine
+            .insert_template_must(
+                "second.html",
+                "(* import \"first.html\" as first *)(* macro b() *)b(* end *)",
+            )
+            .unwrap();
+        engine
+            .insert_template_must(
+                "page.html",
+                "(* import \"first.html\" as first *)(( first.a() ))",
+            )
+            .unwrap();
+        let template = engine.get_template("page.html").unwrap();
+
+        assert_eq!(
+            engine.render(template, &Store::new()),
+            Err(Error::build("invalid import")
+                .with_pointer(
+                    "(* import \"first.html\" as first *)(* macro b() *)b(* end *)",
+                    0..35
+                )
+                .with_help(
+                    "template `first.html` cannot be imported here, because it imports itself \
+                    through `page.html -> first.html -> second.html -> first.html`"
+                )
+                .with_name("second.html"))
+        );
+        assert!(engine
+            .render(
+                &engine
+                    .compile("(* import \"first.html\" as first *)(( first.a() ))")
+                    .unwrap(),
+                &Store::new()
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_render_undefined_shallow() {
+        let engine = Engine::default();
+        let store = Store::new().with_must("user", json!({"name": "taylor", "tags": []}));
+        let template = engine
+            .compile(
+                "(* if user.nickname *)(( user.nickname ))(* else *)(( user.name ))(* end *)\
+                |(( user.tags[0] ))|(( user.name.0 ))",
+            )
+            .unwrap();
+
+        assert_eq!(engine.get_undefined(), Undefined::Shallow);
+        assert_eq!(engine.render(&template, &store).unwrap(), "taylor||");
+
+        let template = engine.compile("(( person.name ))").unwrap();
+        assert_eq!(
+            engine.render(&template, &store),
+            Err(Error::build("missing store value")
+                .with_pointer("(( person.name ))", 3..9)
+                .with_help(
+                    "unable to find `person` in store, \
+                    ensure it exists or try wrapping with an `if` block"
+                ))
+        );
+    }
+
+    #[test]
+    fn test_render_undefined_strict() {
+        let engine = Engine::default().with_undefined(Undefined::Strict);
+        let store = Store::new().with_must("user", json!({"name": "taylor", "tags": []}));
+
+        for (source, region, help) in [
+            ("(( person ))", 3..9, "unable to find `person` in store"),
+            ("(( user.nmae ))", 8..12, "unable to find `nmae` in `user`"),
+            (
+                "(( user.tags[0] ))",
+                12..15,
+                "unable to find `[0]` in `user.tags`",
+            ),
+            (
+                "(( user.name.0 ))",
+                13..14,
+                "unable to find `0` in `user.name`",
+            ),
+        ] {
+            let template = engine.compile(source).unwrap();
+
+            assert_eq!(
+                engine.render(&template, &store),
+                Err(Error::build("missing store value")
+                    .with_pointer(source, region)
+                    .with_help(format!(
+                        "{help}, ensure it exists or try wrapping with an `if` block"
+                    )))
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_undefined_debug() {
+        let mut engine = Engine::default();
+        engine.set_undefined(Undefined::Debug);
+        let template = engine
+            .compile(
+                "(( user.name ))|(( user.nmae ))|(( person | safe ))|\
+                (* if user.nmae *)a(* else *)b(* end *)(( user.name ))",
+            )
+            .unwrap();
+        let store = Store::new().with_must("user", json!({"name": "taylor"}));
+
+        assert_eq!(
+            engine.render(&template, &store).unwrap(),
+            "taylor|[undefined: user.nmae]|[undefined: person]|btaylor"
+        );
+    }
+
+    #[test]
+    fn test_render_undefined_debug_fallback() {
+        let mut engine = Engine::default();
+        engine.set_undefined(Undefined::Debug);
+        let template = engine
+            .compile(
+                "(( missing ?? \"x\" ))|(( (missing) ?? \"y\" ))|\
+                (( \"a\" if missing.name else \"b\" ))|(( user.nmae if user.admin else \"c\" ))|\
+                (( missing ?? user.nmae ))|(( user.name if user.admin ))",
+            )
+            .unwrap();
+        let store = Store::new().with_must("user", json!({"name": "taylor"}));
+
+        assert_eq!(
+            engine.render(&template, &store).unwrap(),
+            "x|y|b|c|[undefined: user.nmae]|[undefined: user.admin]"
+        );
+    }
+
+    #[test]
+    fn test_render_fallback() {
+        let (template, engine) = get_template_with_engine(
+            "(( user.nickname ?? user.name ?? \"anon\" ))|(( guest.name ?? \"anon\" ))|\
+            (( user.email ?? \"none\" ))|(( user.age ?? 0 + 1 ))|(( missing ?? other ?? 2 ))",
+        );
+        let store = Store::new().with_must("user", json!({"name": "taylor", "email": null}));
+
+        assert_eq!(
+            engine.render(&template, &store).unwrap(),
+            "taylor|anon|none|1|2"
+        );
+    }
+
+    #[test]
+    fn test_render_fallback_missing() {
+        let (template, engine) = get_template_with_engine("(( first ?? second ))");
+
+        assert!(engine.render(&template, &Store::new()).is_err());
+    }
+
+    #[test]
+    fn test_render_if_or() {
+        let (template, engine) =
+            get_template_with_engine("(* if a && b || a *)yes(* else *)no(* end *)");
+        let store = Store::new().with_must("a", true).with_must("b", false);
+
+        assert_eq!(engine.render(&template, &store).unwrap(), "yes");
+    }
+
+    #[test]
+    fn test_render_if_group() {
+        let (template, engine) = get_template_with_engine(
+            "(* if a && (b || c) *)1(* end *)|\
+            (* if not (b || c) *)2(* end *)|\
+            (* if not b && c *)3(* end *)|\
+            (* if (count + 1) * 2 == 6 && (a) *)4(* end *)|\
+            (* if b && missing *)5(* else *)6(* end *)|\
+            (* if ((b || c) && a) *)7(* else *)8(* end *)|\
+            (* if ((a)) *)9(* end *)",
+        );
+        let store = Store::new()
+            .with_must("a", true)
+            .with_must("b", false)
+            .with_must("c", false)
+            .with_must("count", 2);
+
+        assert_eq!(engine.render(&template, &store).unwrap(), "|2||4|6|8|9");
+    }
+
+    #[test]
+    fn test_render_conditional() {
+        let mut engine = Engine::default();
+        let card = engine.compile("(( class ))").unwrap();
+        engine.add_template("card", card);
+        let template = engine
+            .compile(
+                "(( \"active\" if selected else \"\" ))|(( \"a\" if count > 2 || not selected ))|\
+                (* let label = name if name != \"\" && selected else \"anon\" *)(( label ))|\
+                (( \"x\" if not selected else \"y\" if count == 2 else \"z\" ))|\
+                (* include card class: \"on\" if selected else \"off\" *)",
+            )
+            .unwrap();
+        let store = Store::new()
+            .with_must("selected", true)
+            .with_must("count", 2)
+            .with_must("name", "taylor");
+
+        assert_eq!(
+            engine.render(&template, &store).unwrap(),
+            "active||taylor|y|on"
+        );
+    }
+
+    #[test]
+    fn test_let_global_scope_if() {
+        let (template, mut engine) = get_template_with_engine(
+            "(* if is_admin *)\
                 (* let name = \"admin\" *)\
             (* else *)\
                 (* let name = user.name | to_lowercase *)\
@@ -751,6 +2126,181 @@ mod tests {
         assert!(renderer.blocks.get("three").is_none());
     }
 
+    #[test]
+    fn test_render_output_escape() {
+        let mut engine = Engine::default();
+        engine
+            .insert_template_must(
+                "page.html",
+                "<p>(( name ))</p>(( name | safe ))(( name | to_lowercase ))",
+            )
+            .unwrap();
+        engine.add_filter_must("to_lowercase", to_lowercase);
+        let template = engine.get_template("page.html").unwrap();
+        let store = Store::new().with_must("name", "<B>Tom & Jerry</B>");
+
+        assert_eq!(
+            engine.render(template, &store).unwrap(),
+            "<p>&lt;B&gt;Tom &amp; Jerry&lt;/B&gt;</p>\
+            <B>Tom & Jerry</B>\
+            &lt;b&gt;tom &amp; jerry&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn test_render_output_escape_safe() {
+        let mut engine = Engine::default();
+        engine
+            .insert_template_must(
+                "page.html",
+                "(* macro icon() *)<i>(( name ))</i>(* end *)\
+                (( icon() ?? \"\" ))|(( icon() if admin else \"-\" ))|(( icon() if admin ))|\
+                (( markup ))|(( markup.0 ?? name ))|(( markup ?? name ))|\
+                (* for markup in [name] *)(( markup ))(* end *)",
+            )
+            .unwrap();
+        let template = engine.get_template("page.html").unwrap();
+        let store = Store::new()
+            .with_must("name", "<b>")
+            .with_must("admin", true)
+            .with_must("markup", "<hr>")
+            .with_safe("markup");
+
+        assert_eq!(
+            engine.render(template, &store).unwrap(),
+            "<i>&lt;b&gt;</i>|<i>&lt;b&gt;</i>|<i>&lt;b&gt;</i>|<hr>|&lt;b&gt;|&lt;hr&gt;|&lt;b&gt;"
+        );
+    }
+
+    #[test]
+    fn test_render_output_escape_safe_binding() {
+        let mut engine = Engine::default();
+        engine
+            .insert_template_must("card.html", "<p>(( body ))</p>")
+            .unwrap();
+        engine
+            .insert_template_must(
+                "page.html",
+                "(* macro show(x, y=markup) *)(( x ))(( y ))(* end *)\
+                (* for icon in icons *)(( icon ))(* end *)|\
+                (* for key, icon in icons *)(( icon ))(* end *)|\
+                (* let safe = markup *)(( safe ))|(* let unsafe = name *)(( unsafe ))|\
+                (( show(markup) ))|(( show(name, name) ))|\
+                (* include \"card.html\" body: markup *)|(* include \"card.html\" body: name *)|\
+                (* for markup in [name] *)(( markup ))(* end *)",
+            )
+            .unwrap();
+        let template = engine.get_template("page.html").unwrap();
+        let store = Store::new()
+            .with_must("name", "<b>")
+            .with_must("markup", "<hr>")
+            .with_must("icons", json!(["<i>"]))
+            .with_safe("markup")
+            .with_safe("icons");
+
+        assert_eq!(
+            engine.render(template, &store).unwrap(),
+            "<i>|<i>|<hr>|&lt;b&gt;|<hr><hr>|&lt;b&gt;&lt;b&gt;|\
+            <p><hr></p>|<p>&lt;b&gt;</p>|&lt;b&gt;"
+        );
+    }
+
+    #[test]
+    fn test_render_macro_escape_safe() {
+        let mut engine = Engine::default();
+        engine.add_filter_must("to_lowercase", to_lowercase);
+        engine
+            .insert_template_must(
+                "page.html",
+                "(* macro button(label) *)<button>(( label ))</button>(* end *)\
+                (* let x = button(\"A\") *)(( x ))|\
+                (( button(\"A\") | to_lowercase ))",
+            )
+            .unwrap();
+        let template = engine.get_template("page.html").unwrap();
+
+        assert_eq!(
+            engine.render(template, &Store::new()).unwrap(),
+            "<button>A</button>|&lt;button&gt;a&lt;/button&gt;"
+        );
+    }
+
+    #[test]
+    fn test_render_output_escape_filter() {
+        let mut engine = Engine::default();
+        engine.add_filter_must("to_lowercase", to_lowercase);
+        engine
+            .insert_template_must(
+                "page.html",
+                "(( markup | safe | to_lowercase ))|(( \"<I>\" | to_lowercase ))|\
+                (( markup | to_lowercase | safe ))",
+            )
+            .unwrap();
+        let template = engine.get_template("page.html").unwrap();
+        let store = Store::new()
+            .with_must("markup", "<B>HI</B>")
+            .with_safe("markup");
+
+        assert_eq!(
+            engine.render(template, &store).unwrap(),
+            "&lt;b&gt;hi&lt;/b&gt;|&lt;i&gt;|<b>hi</b>"
+        );
+    }
+
+    #[test]
+    fn test_render_macro_escape() {
+        let mut engine = Engine::default();
+        engine
+            .insert_template_must("macros.txt", "(* macro bold(x) *)<b>(( x ))</b>(* end *)")
+            .unwrap();
+        engine
+            .insert_template_must(
+                "page.html",
+                "(* from \"macros.txt\" import bold *)(( bold(name) ))",
+            )
+            .unwrap();
+        engine
+            .insert_template_must(
+                "page.txt",
+                "(* from \"macros.txt\" import bold *)(( bold(name) ))",
+            )
+            .unwrap();
+        let store = Store::new().with_must("name", "<script>");
+
+        assert_eq!(
+            engine
+                .render(engine.get_template("page.html").unwrap(), &store)
+                .unwrap(),
+            "<b>&lt;script&gt;</b>"
+        );
+        assert_eq!(
+            engine
+                .render(engine.get_template("page.txt").unwrap(), &store)
+                .unwrap(),
+            "<b><script></b>"
+        );
+    }
+
+    #[test]
+    fn test_render_output_escape_mode() {
+        let engine = Engine::default().with_escape(Escape::Html);
+        let template = engine.compile("<p>(( name ))</p>").unwrap();
+        let store = Store::new().with_must("name", "<b>");
+
+        assert_eq!(
+            engine.render(&template, &store).unwrap(),
+            "<p>&lt;b&gt;</p>"
+        );
+
+        let mut engine = Engine::default().with_escape(Escape::Never);
+        engine
+            .insert_template_must("page.html", "<p>(( name ))</p>")
+            .unwrap();
+        let template = engine.get_template("page.html").unwrap();
+
+        assert_eq!(engine.render(template, &store).unwrap(), "<p><b></p>");
+    }
+
     #[test]
     fn test_evaluate_arguments_increment() {
         let (template, engine) = get_template_with_engine("");
@@ -0,0 +1,196 @@
+use crate::compile::Operator;
+
+use super::{filter::Error, INCOMPATIBLE_TYPES};
+
+use serde_json::{Number, Value};
+
+/// Apply the arithmetic [`Operator`] to the two [`Value`] instances and return
+/// the result.
+///
+/// Numbers support every arithmetic `Operator`. When both numbers are integers
+/// and the result can be represented exactly, the result is an integer, otherwise
+/// it is a float.
+///
+/// Strings and arrays support [`Operator::Add`] to concatenate them, and objects
+/// support it to merge them, preferring the values on the right.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the `Operator` cannot be applied to the types,
+/// a number is divided by zero, or the result is not a finite number.
+pub fn calculate(left: &Value, operator: Operator, right: &Value) -> Result<Value, Error> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => calculate_numbers(left, operator, right),
+        (Value::String(left), Value::String(right)) => match operator {
+            Operator::Add => Ok(Value::String(format!("{left}{right}"))),
+            unsupported => Err(error_unsupported(unsupported, "string")),
+        },
+        (Value::Array(left), Value::Array(right)) => match operator {
+            Operator::Add => Ok(Value::Array(
+                left.iter().chain(right.iter()).cloned().collect(),
+            )),
+            unsupported => Err(error_unsupported(unsupported, "array")),
+        },
+        (Value::Object(left), Value::Object(right)) => match operator {
+            Operator::Add => {
+                let mut merged = left.clone();
+                merged.extend(right.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+                Ok(Value::Object(merged))
+            }
+            unsupported => Err(error_unsupported(unsupported, "object")),
+        },
+        (left, right) => Err(Error::build(INCOMPATIBLE_TYPES).with_help(format!(
+            "operator `{operator}` cannot be applied to `{left}` and `{right}`"
+        ))),
+    }
+}
+
+/// Apply the arithmetic [`Operator`] to the two [`Number`] instances.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the right `Number` is zero and the `Operator` is
+/// [`Operator::Divide`], or the result is not a finite number.
+fn calculate_numbers(left: &Number, operator: Operator, right: &Number) -> Result<Value, Error> {
+    if operator == Operator::Divide && right.as_f64() == Some(0.0) {
+        return Err(Error::build("division by zero")
+            .with_help(format!("unable to divide `{left}` by zero")));
+    }
+
+    if let (Some(left), Some(right)) = (left.as_i64(), right.as_i64()) {
+        let exact = match operator {
+            Operator::Add => left.checked_add(right),
+            Operator::Subtract => left.checked_sub(right),
+            Operator::Multiply => left.checked_mul(right),
+            Operator::Divide => (left.checked_rem(right) == Some(0))
+                .then(|| left.checked_div(right))
+                .flatten(),
+            _ => unreachable!("operator must be arithmetic"),
+        };
+        if let Some(exact) = exact {
+            return Ok(Value::from(exact));
+        }
+    }
+
+    let left_as = left.as_f64().unwrap();
+    let right_as = right.as_f64().unwrap();
+    let result = match operator {
+        Operator::Add => left_as + right_as,
+        Operator::Subtract => left_as - right_as,
+        Operator::Multiply => left_as * right_as,
+        Operator::Divide => left_as / right_as,
+        _ => unreachable!("operator must be arithmetic"),
+    };
+
+    Number::from_f64(result).map(Value::Number).ok_or_else(|| {
+        Error::build("invalid number").with_help(format!(
+            "result of `{left} {operator} {right}` is not a finite number"
+        ))
+    })
+}
+
+/// Return an [`Error`] explaining that the [`Operator`] is invalid on the type.
+fn error_unsupported(operator: Operator, kind: &str) -> Error {
+    Error::build(INCOMPATIBLE_TYPES)
+        .with_help(format!("operator `{operator}` is invalid on {kind} types"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{compile::Operator, log::Error, Engine, Store};
+
+    use super::calculate;
+
+    use serde_json::json;
+
+    #[test]
+    fn test_calculate_numbers() {
+        assert_eq!(calculate(&json!(2), Operator::Add, &json!(3)), Ok(json!(5)));
+        assert_eq!(
+            calculate(&json!(2), Operator::Subtract, &json!(3)),
+            Ok(json!(-1))
+        );
+        assert_eq!(
+            calculate(&json!(2.5), Operator::Multiply, &json!(2)),
+            Ok(json!(5.0))
+        );
+        assert_eq!(
+            calculate(&json!(9), Operator::Divide, &json!(3)),
+            Ok(json!(3))
+        );
+        assert_eq!(
+            calculate(&json!(7), Operator::Divide, &json!(2)),
+            Ok(json!(3.5))
+        );
+        assert_eq!(
+            calculate(&json!(i64::MAX), Operator::Add, &json!(1)),
+            Ok(json!(i64::MAX as f64 + 1.0))
+        );
+    }
+
+    #[test]
+    fn test_calculate_divide_zero() {
+        assert_eq!(
+            calculate(&json!(1), Operator::Divide, &json!(0)),
+            Err(Error::build("division by zero").with_help("unable to divide `1` by zero"))
+        );
+        assert!(calculate(&json!(1), Operator::Divide, &json!(0.0)).is_err());
+    }
+
+    #[test]
+    fn test_calculate_concat() {
+        assert_eq!(
+            calculate(&json!("a"), Operator::Add, &json!("b")),
+            Ok(json!("ab"))
+        );
+        assert_eq!(
+            calculate(&json!([1]), Operator::Add, &json!([2, 3])),
+            Ok(json!([1, 2, 3]))
+        );
+        assert_eq!(
+            calculate(&json!({"a": 1, "b": 2}), Operator::Add, &json!({"b": 3})),
+            Ok(json!({"a": 1, "b": 3}))
+        );
+        assert!(calculate(&json!("a"), Operator::Subtract, &json!("b")).is_err());
+        assert!(calculate(&json!("a"), Operator::Add, &json!(1)).is_err());
+    }
+
+    #[test]
+    fn test_render_arithmetic() {
+        let engine = Engine::default();
+        let template = engine
+            .compile(
+                "(( price * quantity + 1 ))|(( (price + 1) * quantity ))|\
+                ((price - (quantity - 1)))|(( name + \"!\" ))|\
+                (* let total = price * quantity / 2 *)(( total ))",
+            )
+            .unwrap();
+        let store = Store::new()
+            .with_must("price", 4)
+            .with_must("quantity", 3)
+            .with_must("name", "taylor");
+
+        assert_eq!(
+            engine.render(&template, &store).unwrap(),
+            "13|15|2|taylor!|6"
+        );
+    }
+
+    #[test]
+    fn test_render_arithmetic_base() {
+        let engine = Engine::default();
+        let template = engine
+            .compile(
+                "(* for item in first + second *)(( item ))(* end *)|\
+                (* if price * 2 > 7 && price - 4 == 0 *)a(* end *)",
+            )
+            .unwrap();
+        let store = Store::new()
+            .with_must("first", json!([1, 2]))
+            .with_must("second", json!([3]))
+            .with_must("price", 4);
+
+        assert_eq!(engine.render(&template, &store).unwrap(), "123|a");
+    }
+}
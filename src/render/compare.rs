@@ -1,8 +1,10 @@
+use std::cmp::Ordering;
+
 use crate::compile::Operator;
 
-use super::{filter::Error, INCOMPATIBLE_TYPES};
+use super::{arithmetic::calculate, filter::Error, INCOMPATIBLE_TYPES};
 
-use serde_json::{json, Value};
+use serde_json::{Number, Value};
 
 /// Return true if the [`Value`] is truthy.
 pub fn is_truthy(value: &Value) -> bool {
@@ -18,95 +20,212 @@ pub fn is_truthy(value: &Value) -> bool {
 
 /// Compare the two [`Value`] instances with the given [`Operator`].
 ///
+/// When the `Operator` is arithmetic, the result is calculated and its
+/// truthiness is returned. When the `Operator` is [`Operator::In`] or
+/// [`Operator::NotIn`], the right `Value` is searched for the left `Value`.
+///
+/// Values of different types are never equal, so `null` is only equal to
+/// `null`. When `coerce` is true, a string that contains a number is compared
+/// with a number as that number, so `1 == "1"`.
+///
+/// Arrays and objects are equal when their contents are equal. Arrays are
+/// ordered by their first unequal element, then by their length, while
+/// objects cannot be ordered.
+///
 /// # Errors
 ///
-/// Returns an [`Error`] if the two types cannot be compared, or the `Operator`
+/// Returns an [`Error`] if the two types cannot be ordered, or the `Operator`
 /// cannot be applied to the types.
-pub fn compare_values(left: &Value, operator: Operator, right: &Value) -> Result<bool, Error> {
-    let result = match (left, right) {
-        (Value::Number(left), Value::Number(right)) => {
-            let left_as = left.as_f64().unwrap();
-            let right_as = right.as_f64().unwrap();
-            match operator {
-                Operator::Add => is_truthy(&json!(left_as + right_as)),
-                Operator::Subtract => is_truthy(&json!(left_as - right_as)),
-                Operator::Multiply => is_truthy(&json!(left_as * right_as)),
-                Operator::Divide => is_truthy(&json!(left_as / right_as)),
-                Operator::Greater => left_as > right_as,
-                Operator::Lesser => left_as < right_as,
-                Operator::Equal => left_as == right_as,
-                Operator::NotEqual => left_as != right_as,
-                Operator::GreaterOrEqual => left_as >= right_as,
-                Operator::LesserOrEqual => left_as <= right_as,
-            }
-        }
-        (Value::String(left), Value::String(right)) => match operator {
-            Operator::Add => is_truthy(&Value::String(format!("{left}{right}"))),
-            Operator::Greater => left > right,
-            Operator::Lesser => left < right,
-            Operator::Equal => left == right,
-            Operator::NotEqual => left != right,
-            Operator::GreaterOrEqual => left >= right,
-            Operator::LesserOrEqual => left <= right,
-            unsupported => {
-                return Err(Error::build(INCOMPATIBLE_TYPES).with_help(format!(
-                    "operator `{unsupported}` is invalid on string types"
-                )))
+pub fn compare_values(
+    left: &Value,
+    operator: Operator,
+    right: &Value,
+    coerce: bool,
+) -> Result<bool, Error> {
+    if operator.get_precedence().is_some() {
+        return calculate(left, operator, right).map(|value| is_truthy(&value));
+    }
+    match operator {
+        Operator::In => return contains(right, left),
+        Operator::NotIn => return contains(right, left).map(|found| !found),
+        _ => {}
+    }
+    if coerce {
+        match (left, right) {
+            (Value::Number(_), Value::String(string)) => {
+                if let Some(number) = parse_number(string) {
+                    return compare_values(left, operator, &Value::Number(number), false);
+                }
             }
-        },
-        (Value::Bool(left), Value::Bool(right)) => match operator {
-            Operator::Greater => left > right,
-            Operator::Lesser => left < right,
-            Operator::Equal => left == right,
-            Operator::NotEqual => left != right,
-            Operator::GreaterOrEqual => left >= right,
-            Operator::LesserOrEqual => left <= right,
-            unsupported => {
-                return Err(Error::build(INCOMPATIBLE_TYPES).with_help(format!(
-                    "operator `{unsupported}` is invalid on boolean types"
-                )))
+            (Value::String(string), Value::Number(_)) => {
+                if let Some(number) = parse_number(string) {
+                    return compare_values(&Value::Number(number), operator, right, false);
+                }
             }
-        },
-        (Value::Array(left), Value::Array(right)) => match operator {
-            Operator::Add => is_truthy(&json!(left.len() + right.len())),
-            Operator::Subtract => is_truthy(&json!(left.len() - right.len())),
-            Operator::Multiply => is_truthy(&json!(left.len() * right.len())),
-            Operator::Divide => is_truthy(&json!(left.len() / right.len())),
-            Operator::Greater => left.len() > right.len(),
-            Operator::Lesser => left.len() < right.len(),
-            Operator::Equal => left.len() == right.len(),
-            Operator::NotEqual => left.len() != right.len(),
-            Operator::GreaterOrEqual => left.len() >= right.len(),
-            Operator::LesserOrEqual => left.len() <= right.len(),
-        },
-        (Value::Object(left), Value::Object(right)) => match operator {
-            Operator::Add => is_truthy(&json!(left.len() + right.len())),
-            Operator::Subtract => is_truthy(&json!(left.len() - right.len())),
-            Operator::Multiply => is_truthy(&json!(left.len() * right.len())),
-            Operator::Divide => is_truthy(&json!(left.len() / right.len())),
-            Operator::Greater => left.len() > right.len(),
-            Operator::Lesser => left.len() < right.len(),
-            Operator::Equal => left.len() == right.len(),
-            Operator::NotEqual => left.len() != right.len(),
-            Operator::GreaterOrEqual => left.len() >= right.len(),
-            Operator::LesserOrEqual => left.len() <= right.len(),
-        },
-        (left, right) => {
-            return Err(Error::build(INCOMPATIBLE_TYPES).with_help(format!(
-                "types `{}` and `{}` cannot be compared",
-                left, right
-            )))
+            _ => {}
         }
+    }
+
+    let result = match operator {
+        Operator::Equal => is_equal(left, right),
+        Operator::NotEqual => !is_equal(left, right),
+        Operator::Greater => order_values(left, right)?.is_gt(),
+        Operator::Lesser => order_values(left, right)?.is_lt(),
+        Operator::GreaterOrEqual => order_values(left, right)?.is_ge(),
+        Operator::LesserOrEqual => order_values(left, right)?.is_le(),
+        _ => unreachable!("arithmetic operator must be calculated"),
     };
 
     Ok(result)
 }
 
+/// Return true if the two [`Value`] instances are structurally equal.
+///
+/// Numbers are equal when they have the same value, so `1` is equal to `1.0`,
+/// and arrays and objects are equal when their contents are equal.
+fn is_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => order_numbers(left, right).is_eq(),
+        (Value::Array(left), Value::Array(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right)
+                    .all(|(left, right)| is_equal(left, right))
+        }
+        (Value::Object(left), Value::Object(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .all(|(key, left)| right.get(key).is_some_and(|right| is_equal(left, right)))
+        }
+        (left, right) => left == right,
+    }
+}
+
+/// Return the [`Ordering`] of the two [`Value`] instances.
+///
+/// Arrays are ordered lexicographically, by their first unequal element, and
+/// then by their length.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the two types cannot be ordered, such as objects or
+/// values of different types.
+fn order_values(left: &Value, right: &Value) -> Result<Ordering, Error> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => Ok(order_numbers(left, right)),
+        (Value::String(left), Value::String(right)) => Ok(left.cmp(right)),
+        (Value::Bool(left), Value::Bool(right)) => Ok(left.cmp(right)),
+        (Value::Array(left), Value::Array(right)) => {
+            for (left, right) in left.iter().zip(right) {
+                match order_values(left, right)? {
+                    Ordering::Equal => continue,
+                    ordering => return Ok(ordering),
+                }
+            }
+
+            Ok(left.len().cmp(&right.len()))
+        }
+        (Value::Object(_), Value::Object(_)) => Err(Error::build(INCOMPATIBLE_TYPES).with_help(
+            "objects cannot be ordered, try comparing their length with the `length` filter",
+        )),
+        (left, right) => Err(Error::build(INCOMPATIBLE_TYPES)
+            .with_help(format!("`{}` and `{}` cannot be ordered", left, right))),
+    }
+}
+
+/// Return the [`Ordering`] of the two [`Number`] instances.
+///
+/// Integers are compared exactly, even when they are too large to be
+/// represented by a float.
+fn order_numbers(left: &Number, right: &Number) -> Ordering {
+    match (as_integer(left), as_integer(right)) {
+        (Some(left), Some(right)) => left.cmp(&right),
+        (Some(left), None) => order_integer_float(left, right.as_f64().unwrap()),
+        (None, Some(right)) => order_integer_float(right, left.as_f64().unwrap()).reverse(),
+        (None, None) => {
+            let (left, right) = (left.as_f64().unwrap(), right.as_f64().unwrap());
+            left.partial_cmp(&right).expect("number must be finite")
+        }
+    }
+}
+
+/// Return the integer within the [`Number`], or [`None`] if it is a float.
+fn as_integer(number: &Number) -> Option<i128> {
+    number
+        .as_i64()
+        .map(i128::from)
+        .or_else(|| number.as_u64().map(i128::from))
+}
+
+/// Return the [`Ordering`] of an integer and a float, without converting the
+/// integer to a float.
+fn order_integer_float(integer: i128, float: f64) -> Ordering {
+    // Each integer is within this bound, where a truncated float is exact.
+    const BOUND: f64 = 18_446_744_073_709_551_616.0;
+
+    if float >= BOUND {
+        return Ordering::Less;
+    }
+    if float <= -BOUND {
+        return Ordering::Greater;
+    }
+    let truncated = float.trunc();
+
+    integer
+        .cmp(&(truncated as i128))
+        .then_with(|| 0.0.partial_cmp(&(float - truncated)).unwrap())
+}
+
+/// Parse the number within the string, ignoring surrounding whitespace.
+///
+/// Returns [`None`] if the string does not contain a finite number.
+fn parse_number(string: &str) -> Option<Number> {
+    let string = string.trim();
+
+    string
+        .parse::<i64>()
+        .map(Number::from)
+        .or_else(|_| string.parse::<u64>().map(Number::from))
+        .ok()
+        .or_else(|| string.parse::<f64>().ok().and_then(Number::from_f64))
+}
+
+/// Return true if the container [`Value`] contains the item [`Value`].
+///
+/// Arrays contain any equal element, objects contain their keys, and strings
+/// contain any substring.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the container is not an array, object or string,
+/// or the item cannot be searched for within the container.
+fn contains(container: &Value, item: &Value) -> Result<bool, Error> {
+    match (container, item) {
+        (Value::Array(array), item) => Ok(array.iter().any(|element| is_equal(element, item))),
+        (Value::Object(object), Value::String(key)) => Ok(object.contains_key(key)),
+        (Value::String(string), Value::String(substring)) => {
+            Ok(string.contains(substring.as_str()))
+        }
+        (Value::Object(_), item) => Err(Error::build(INCOMPATIBLE_TYPES)
+            .with_help(format!("object keys are strings, unable to find `{item}`"))),
+        (Value::String(_), item) => Err(Error::build(INCOMPATIBLE_TYPES).with_help(format!(
+            "only a string can be found within a string, found `{item}`"
+        ))),
+        (container, _) => Err(Error::build(INCOMPATIBLE_TYPES).with_help(format!(
+            "operator `in` requires an array, object or string, found `{container}`"
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{compile::Operator, Engine, Store};
     use serde_json::{json, Value};
 
+    use super::compare_values;
+
     #[test]
     fn test_truthy_boolean() {
         let engine = Engine::default();
@@ -172,48 +291,36 @@ mod tests {
 
     #[test]
     fn test_truthy_subtract() {
-        let left = vec![
-            json!(20),
-            json!(["one", "two"]),
-            json!({"a": "b", "c": "d"}),
-        ];
-        let right = vec![json!(10), json!(["one"]), json!({"a": "b"})];
+        let left = vec![json!(20), json!(0.5)];
+        let right = vec![json!(10), json!(0.25)];
         test_truthy_compare(left, right, Operator::Subtract);
     }
 
     #[test]
     fn test_truthy_multiply() {
-        let left = vec![
-            json!(10),
-            json!(["one", "two"]),
-            json!({"a": "b", "c": "d"}),
-        ];
-        let right = vec![json!(10), json!(["one"]), json!({"a": "b"})];
+        let left = vec![json!(10), json!(0.5)];
+        let right = vec![json!(10), json!(3)];
         test_truthy_compare(left, right, Operator::Multiply);
     }
 
     #[test]
     fn test_truthy_divide() {
-        let left = vec![
-            json!(100),
-            json!(["one", "two"]),
-            json!({"a": "b", "c": "d"}),
-        ];
-        let right = vec![json!(10), json!(["one"]), json!({"a": "b"})];
+        let left = vec![json!(100), json!(1)];
+        let right = vec![json!(10), json!(4)];
         test_truthy_compare(left, right, Operator::Divide);
     }
 
     #[test]
     fn test_truthy_greater() {
-        let left = vec![json!(100), json!("b"), json!(true)];
-        let right = vec![json!(50), json!("a"), json!(false)];
+        let left = vec![json!(100), json!("b"), json!(true), json!(["b"])];
+        let right = vec![json!(50), json!("a"), json!(false), json!(["a", "c"])];
         test_truthy_compare(left, right, Operator::Greater);
     }
 
     #[test]
     fn test_truthy_lesser() {
-        let left = vec![json!(50), json!("a"), json!(false)];
-        let right = vec![json!(5100), json!("b"), json!(true)];
+        let left = vec![json!(50), json!("a"), json!(false), json!([1, 2])];
+        let right = vec![json!(5100), json!("b"), json!(true), json!([1, 2, 0])];
         test_truthy_compare(left, right, Operator::Lesser);
     }
 
@@ -225,6 +332,8 @@ mod tests {
             json!(true),
             json!(["one"]),
             json!({"a": "b"}),
+            json!(1),
+            json!([1, {"a": [2]}]),
         ];
         let right = vec![
             json!(10),
@@ -232,6 +341,8 @@ mod tests {
             json!(true),
             json!(["one"]),
             json!({"a": "b"}),
+            json!(1.0),
+            json!([1.0, {"a": [2]}]),
         ];
         test_truthy_compare(left, right, Operator::Equal);
     }
@@ -244,6 +355,8 @@ mod tests {
             json!(true),
             json!(["one"]),
             json!({"a": "b"}),
+            json!(["a"]),
+            json!({"a": 1}),
         ];
         let right = vec![
             json!(20),
@@ -251,6 +364,8 @@ mod tests {
             json!(false),
             json!(["one", "two"]),
             json!({"a": "b", "c": "d"}),
+            json!(["b"]),
+            json!({"b": 2}),
         ];
         test_truthy_compare(left, right, Operator::NotEqual);
     }
@@ -266,8 +381,6 @@ mod tests {
             json!(true),
             json!(["one"]),
             json!(["one", "two"]),
-            json!({"a": "b"}),
-            json!({"a": "b", "c": "d"}),
         ];
         let right = vec![
             json!(10),
@@ -278,8 +391,6 @@ mod tests {
             json!(false),
             json!(["one"]),
             json!(["one"]),
-            json!({"a": "b"}),
-            json!({"a": "b"}),
         ];
         test_truthy_compare(left, right, Operator::GreaterOrEqual);
     }
@@ -295,8 +406,6 @@ mod tests {
             json!(false),
             json!(["one"]),
             json!(["one"]),
-            json!({"a": "b"}),
-            json!({"a": "b"}),
         ];
         let right = vec![
             json!(10),
@@ -307,12 +416,197 @@ mod tests {
             json!(true),
             json!(["one"]),
             json!(["one", "two"]),
-            json!({"a": "b"}),
-            json!({"a": "b", "c": "d"}),
         ];
         test_truthy_compare(left, right, Operator::LesserOrEqual);
     }
 
+    #[test]
+    fn incompatible_ordering() {
+        let engine = Engine::default();
+        let template = engine.compile("(* if left > right *)a(* end *)").unwrap();
+        for (left, right) in [
+            (json!({"a": 1}), json!({"b": 2})),
+            (json!([1]), json!(["a"])),
+        ] {
+            let store = Store::new()
+                .with_must("left", left)
+                .with_must("right", right);
+
+            assert!(engine.render(&template, &store).is_err());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "filters")]
+    fn test_render_compare_length() {
+        let engine = Engine::default().with_std_filters();
+        let template = engine
+            .compile(
+                "(* let left = left | length *)(* let right = right | length *)\
+                (* if left > right *)a(* else *)b(* end *)",
+            )
+            .unwrap();
+        let store = Store::new()
+            .with_must("left", json!({"a": 1, "b": 2}))
+            .with_must("right", json!({"c": 3}));
+
+        assert_eq!(engine.render(&template, &store).unwrap(), "a");
+    }
+
+    #[test]
+    fn test_compare_null() {
+        assert_eq!(
+            compare_values(&json!(null), Operator::Equal, &json!(null), false),
+            Ok(true)
+        );
+        assert_eq!(
+            compare_values(&json!("a"), Operator::Equal, &json!(null), false),
+            Ok(false)
+        );
+        assert_eq!(
+            compare_values(&json!(null), Operator::NotEqual, &json!(0), false),
+            Ok(true)
+        );
+        assert!(compare_values(&json!(null), Operator::Greater, &json!(null), false).is_err());
+    }
+
+    #[test]
+    fn test_render_compare_null() {
+        let engine = Engine::default();
+        let template = engine
+            .compile(
+                "(* if value == null *)a(* else *)b(* end *)|\
+                (* if value != null *)a(* else *)b(* end *)",
+            )
+            .unwrap();
+
+        for (value, expect) in [(json!(null), "a|b"), (json!(0), "b|a"), (json!(""), "b|a")] {
+            let store = Store::new().with_must("value", value);
+
+            assert_eq!(engine.render(&template, &store).unwrap(), expect);
+        }
+    }
+
+    #[test]
+    fn test_compare_mixed_types() {
+        assert_eq!(
+            compare_values(&json!(1), Operator::Equal, &json!("1"), false),
+            Ok(false)
+        );
+        assert_eq!(
+            compare_values(&json!([1]), Operator::NotEqual, &json!({"a": 1}), false),
+            Ok(true)
+        );
+        assert!(compare_values(&json!(1), Operator::Lesser, &json!("2"), false).is_err());
+    }
+
+    #[test]
+    fn test_compare_coerce() {
+        assert_eq!(
+            compare_values(&json!(1), Operator::Equal, &json!("1"), true),
+            Ok(true)
+        );
+        assert_eq!(
+            compare_values(&json!(" 10 "), Operator::Greater, &json!(9.5), true),
+            Ok(true)
+        );
+        assert_eq!(
+            compare_values(&json!("one"), Operator::Equal, &json!(1), true),
+            Ok(false)
+        );
+        assert!(compare_values(&json!("one"), Operator::Greater, &json!(1), true).is_err());
+
+        let engine = Engine::default().with_coerce(true);
+        let template = engine
+            .compile("(* if page == \"2\" && \"3\" > page *)a(* end *)")
+            .unwrap();
+
+        assert_eq!(
+            engine
+                .render(&template, &Store::new().with_must("page", 2))
+                .unwrap(),
+            "a"
+        );
+    }
+
+    #[test]
+    fn test_compare_numbers_exact() {
+        let large = json!(9_007_199_254_740_993_i64);
+
+        assert_eq!(
+            compare_values(
+                &large,
+                Operator::Equal,
+                &json!(9_007_199_254_740_992.0),
+                false
+            ),
+            Ok(false)
+        );
+        assert_eq!(
+            compare_values(
+                &large,
+                Operator::Greater,
+                &json!(9_007_199_254_740_992.0),
+                false
+            ),
+            Ok(true)
+        );
+        assert_eq!(
+            compare_values(&json!(u64::MAX), Operator::Greater, &json!(i64::MAX), false),
+            Ok(true)
+        );
+        assert_eq!(
+            compare_values(&json!(u64::MAX), Operator::Lesser, &json!(1e20), false),
+            Ok(true)
+        );
+        assert_eq!(
+            compare_values(&json!(-2), Operator::Lesser, &json!(-1.5), false),
+            Ok(true)
+        );
+        assert_eq!(
+            compare_values(&json!(2.0), Operator::Equal, &json!(2), false),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_truthy_in() {
+        let left = vec![json!("admin"), json!(2), json!("a"), json!("or")];
+        let right = vec![
+            json!(["user", "admin"]),
+            json!([1, 2, 3]),
+            json!({"a": "b"}),
+            json!("lorem"),
+        ];
+        test_truthy_compare(left, right, Operator::In);
+    }
+
+    #[test]
+    fn test_truthy_not_in() {
+        let left = vec![json!("guest"), json!("2"), json!("b"), json!("ip")];
+        let right = vec![
+            json!(["user", "admin"]),
+            json!([1, 2, 3]),
+            json!({"a": "b"}),
+            json!("lorem"),
+        ];
+        test_truthy_compare(left, right, Operator::NotIn);
+    }
+
+    #[test]
+    fn incompatible_container() {
+        let engine = Engine::default();
+        for source in [
+            "(* if 1 in 10 *)a(* end *)",
+            "(* if 1 in \"lorem\" *)a(* end *)",
+            "(* if 1 not in {\"a\": 1} *)a(* end *)",
+        ] {
+            let template = engine.compile(source).unwrap();
+
+            assert!(engine.render(&template, &Store::new()).is_err());
+        }
+    }
+
     // Zip the two Vec<Value> instances together and compare them in a template with the
     // given `Operator`.
     fn test_truthy_compare(left: Vec<Value>, right: Vec<Value>, operator: Operator) {
@@ -0,0 +1,124 @@
+use std::{
+    fmt::{Result, Write},
+    path::Path,
+};
+
+use crate::path::has_extension;
+
+/// Extensions of [`Template`][`crate::Template`] names that are escaped
+/// when the [`Escape`] mode is [`Auto`][`Escape::Auto`].
+const HTML_EXTENSIONS: [&str; 3] = ["html", "htm", "xml"];
+
+/// Describes when the output of an expression is escaped for HTML.
+///
+/// Raw text in a [`Template`][`crate::Template`] is never escaped, only the
+/// values produced by expressions such as `(( name ))`.
+///
+/// An expression may opt out of escaping by ending with a [`Filter`][`crate::filter::Filter`]
+/// that marks its output as safe, such as the built-in `safe` filter:
+///
+/// ```text
+/// (( markup | safe ))
+/// ```
+///
+/// A value can also be marked as safe in the [`Store`][`crate::Store`] with
+/// [`set_safe`][`crate::Store::set_safe`]. String literals and the output of
+/// macros are not escaped, and neither is a fallback or conditional whose
+/// branches are all safe, such as `(( icon() ?? "" ))`.
+///
+/// Safety follows a value when it is bound to a new name, so a loop variable,
+/// `let` binding, macro argument or include argument is safe when the value
+/// it was bound from is safe. A filter only keeps a value safe when it marks
+/// its own output as safe, so `(( icon() | trim ))` is escaped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Escape {
+    /// Escape expressions in templates with a name ending in `.html`, `.htm`
+    /// or `.xml`.
+    ///
+    /// Templates without a name are not escaped.
+    #[default]
+    Auto,
+    /// Escape expressions in every template.
+    Html,
+    /// Never escape expressions.
+    Never,
+}
+
+impl Escape {
+    /// Return true if expressions in the [`Template`][`crate::Template`] with the
+    /// given name should be escaped.
+    pub(crate) fn applies(&self, name: Option<&str>) -> bool {
+        match self {
+            Escape::Auto => {
+                name.is_some_and(|name| has_extension(Path::new(name), &HTML_EXTENSIONS))
+            }
+            Escape::Html => true,
+            Escape::Never => false,
+        }
+    }
+}
+
+/// Wraps some underlying buffer, and escapes characters with special meaning
+/// in HTML before writing them to it.
+pub struct HtmlWriter<'buffer> {
+    /// The underlying buffer.
+    buffer: &'buffer mut (dyn Write + 'buffer),
+}
+
+impl<'buffer> HtmlWriter<'buffer> {
+    /// Create a new `HtmlWriter` that writes to the given buffer.
+    pub fn new(buffer: &'buffer mut (dyn Write + 'buffer)) -> Self {
+        Self { buffer }
+    }
+}
+
+impl Write for HtmlWriter<'_> {
+    fn write_str(&mut self, s: &str) -> Result {
+        let mut last = 0;
+        for (index, char) in s.char_indices() {
+            let escaped = match char {
+                '&' => "&amp;",
+                '<' => "&lt;",
+                '>' => "&gt;",
+                '"' => "&quot;",
+                '\'' => "&#x27;",
+                _ => continue,
+            };
+            self.buffer.write_str(&s[last..index])?;
+            self.buffer.write_str(escaped)?;
+            last = index + 1;
+        }
+
+        self.buffer.write_str(&s[last..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use super::{Escape, HtmlWriter};
+
+    #[test]
+    fn test_html_writer() {
+        let mut buffer = String::new();
+        HtmlWriter::new(&mut buffer)
+            .write_str("<a href=\"/?a=1&b='2'\">link</a>")
+            .unwrap();
+
+        assert_eq!(
+            buffer,
+            "&lt;a href=&quot;/?a=1&amp;b=&#x27;2&#x27;&quot;&gt;link&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_escape_applies() {
+        assert!(Escape::Auto.applies(Some("index.html")));
+        assert!(Escape::Auto.applies(Some("layouts/feed.xml")));
+        assert!(!Escape::Auto.applies(Some("email.txt")));
+        assert!(!Escape::Auto.applies(None));
+        assert!(Escape::Html.applies(None));
+        assert!(!Escape::Never.applies(Some("index.html")));
+    }
+}
@@ -93,6 +93,8 @@
 //! error: filter `to_lowercase` requires string input
 //! ```
 
+#[cfg(feature = "filters")]
+pub mod library;
 pub mod serde {
     //! Contains types from `serde_json`.
     pub use serde_json::*;
@@ -117,6 +119,17 @@ pub trait Filter: Sync + Send {
     ///
     /// May return an [`Error`] to abort template rendering.
     fn apply(&self, input: &Value, args: &HashMap<String, Value>) -> Result<Value, Error>;
+
+    /// Return true if the output of the [`Filter`] is safe to render without
+    /// escaping.
+    ///
+    /// Only the final `Filter` in an expression is consulted, so an expression like
+    /// `(( markup | safe | upper ))` is still escaped.
+    ///
+    /// Returns false by default.
+    fn is_safe(&self) -> bool {
+        false
+    }
 }
 
 /// Allows any function with a matching signature to be registered as a [`Filter`].
@@ -128,3 +141,54 @@ where
         self(value, args)
     }
 }
+
+/// Wraps a [`Filter`] to mark its output as safe to render without escaping.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use ban::{
+///     filter::{
+///         serde::{json, Value},
+///         Error, Safe,
+///     },
+///     Engine, Store,
+/// };
+///
+/// fn bold(value: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+///     Ok(json!(format!("<b>{}</b>", value.as_str().unwrap_or_default())))
+/// }
+///
+/// let mut engine = Engine::default().with_filter_must("bold", Safe(bold));
+/// engine.insert_template_must("name.html", "(( name | bold ))").unwrap();
+/// let template = engine.get_template("name.html").unwrap();
+/// let result = engine.render(template, &Store::new().with_must("name", "taylor"));
+///
+/// assert_eq!(result.unwrap(), "<b>taylor</b>");
+/// ```
+pub struct Safe<F>(pub F);
+
+impl<F> Filter for Safe<F>
+where
+    F: Filter,
+{
+    #[inline]
+    fn apply(&self, input: &Value, args: &HashMap<String, Value>) -> Result<Value, Error> {
+        self.0.apply(input, args)
+    }
+
+    #[inline]
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+/// Return the input [`Value`] unchanged.
+///
+/// Registered as the built-in `safe` [`Filter`], wrapped in [`Safe`] to mark a
+/// value as safe to render without escaping.
+pub(crate) fn identity(input: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+    Ok(input.clone())
+}
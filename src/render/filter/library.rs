@@ -0,0 +1,524 @@
+//! Contains the standard library of [`Filter`][`super::Filter`] functions.
+//!
+//! These filters are available when the `filters` feature is enabled, which it
+//! is by default. Register all of them on an [`Engine`][`crate::Engine`] with
+//! [`with_std_filters`][`crate::Engine::with_std_filters`], or register
+//! individual filters by name with [`with_filter_must`][`crate::Engine::with_filter_must`].
+//!
+//! | Name         | Input                  | Arguments                       |
+//! |--------------|------------------------|---------------------------------|
+//! | `upper`      | string                 |                                 |
+//! | `lower`      | string                 |                                 |
+//! | `capitalize` | string                 |                                 |
+//! | `trim`       | string                 |                                 |
+//! | `replace`    | string                 | `from`, `to`                    |
+//! | `truncate`   | string                 | `length`, `end` (default "...") |
+//! | `escape`     | any                    |                                 |
+//! | `length`     | string, array, object  |                                 |
+//! | `first`      | string, array          |                                 |
+//! | `last`       | string, array          |                                 |
+//! | `reverse`    | string, array          |                                 |
+//! | `join`       | array                  | `separator` (default "")        |
+//! | `default`    | any                    | `value`                         |
+//! | `add`        | number                 | `value`                         |
+//!
+//! Arguments may be given by name, or anonymously in the order shown.
+//!
+//! ## Examples
+//!
+//! ```
+//! use ban::{filter::serde::{json, Value}, Engine, Store};
+//!
+//! let engine = Engine::default().with_std_filters();
+//! let template = engine
+//!     .compile(r#"(( names | join ", " | upper )) (( missing | default "none" ))"#)
+//!     .unwrap();
+//! let store = Store::new()
+//!     .with_must("names", json!(["taylor", "jordan"]))
+//!     .with_must("missing", Value::Null);
+//!
+//! assert_eq!(engine.render(&template, &store).unwrap(), "TAYLOR, JORDAN none");
+//! ```
+
+use std::{collections::HashMap, fmt::Write};
+
+use serde_json::{json, Value};
+
+use crate::{
+    log::Error,
+    render::{escape::HtmlWriter, pipe::Pipe},
+};
+
+/// Return the input string with all characters converted to uppercase.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a string.
+pub fn upper(input: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+    let string = expect_string("upper", input)?;
+
+    Ok(json!(string.to_uppercase()))
+}
+
+/// Return the input string with all characters converted to lowercase.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a string.
+pub fn lower(input: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+    let string = expect_string("lower", input)?;
+
+    Ok(json!(string.to_lowercase()))
+}
+
+/// Return the input string with the first character converted to uppercase.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a string.
+pub fn capitalize(input: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+    let string = expect_string("capitalize", input)?;
+
+    let mut chars = string.chars();
+    let result = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    };
+
+    Ok(json!(result))
+}
+
+/// Return the input string with leading and trailing whitespace removed.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a string.
+pub fn trim(input: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+    let string = expect_string("trim", input)?;
+
+    Ok(json!(string.trim()))
+}
+
+/// Return the input string with every occurrence of the `from` argument
+/// replaced by the `to` argument.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a string, or either argument is
+/// missing or not a string.
+pub fn replace(input: &Value, args: &HashMap<String, Value>) -> Result<Value, Error> {
+    let string = expect_string("replace", input)?;
+    let from = expect_string_argument("replace", args, "from", 1)?;
+    let to = expect_string_argument("replace", args, "to", 2)?;
+
+    Ok(json!(string.replace(from, to)))
+}
+
+/// Return the input string shortened to the number of characters given by
+/// the `length` argument, followed by the `end` argument.
+///
+/// The `end` argument defaults to "...", and is not added when the input is
+/// already short enough.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a string, or the `length` argument
+/// is missing or not a positive integer.
+pub fn truncate(input: &Value, args: &HashMap<String, Value>) -> Result<Value, Error> {
+    let string = expect_string("truncate", input)?;
+    let length = get_argument(args, "length", 1)
+        .and_then(|length| length.as_u64())
+        .ok_or_else(|| {
+            Error::build("filter `truncate` requires argument `length`").with_help(
+                "pass the maximum number of characters as a positive integer, \
+                like `truncate 10`",
+            )
+        })? as usize;
+    let end = match get_argument(args, "end", 2) {
+        Some(_) => expect_string_argument("truncate", args, "end", 2)?,
+        None => "...",
+    };
+
+    if string.chars().count() <= length {
+        return Ok(json!(string));
+    }
+
+    let mut result: String = string.chars().take(length).collect();
+    result.push_str(end);
+
+    Ok(json!(result))
+}
+
+/// Return the input rendered as a string, with characters that have special
+/// meaning in HTML escaped.
+///
+/// The output is marked as safe when registered with
+/// [`with_std_filters`][`crate::Engine::with_std_filters`], so it is not escaped
+/// a second time.
+pub fn escape(input: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+    let mut buffer = String::new();
+    Pipe::new(&mut HtmlWriter::new(&mut buffer))
+        .write_value(input)
+        .map_err(|_| error_write("escape"))?;
+
+    Ok(json!(buffer))
+}
+
+/// Return the number of characters in a string, items in an array, or pairs in
+/// an object.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a string, array or object.
+pub fn length(input: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+    match input {
+        Value::String(string) => Ok(json!(string.chars().count())),
+        Value::Array(array) => Ok(json!(array.len())),
+        Value::Object(object) => Ok(json!(object.len())),
+        _ => Err(
+            Error::build("filter `length` requires string, array or object input")
+                .with_help(format!("unable to get length of `{input}`")),
+        ),
+    }
+}
+
+/// Return the first character of a string, or first item of an array.
+///
+/// Returns null when the input is empty.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a string or array.
+pub fn first(input: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+    match input {
+        Value::String(string) => Ok(string.chars().next().map_or(Value::Null, |c| json!(c))),
+        Value::Array(array) => Ok(array.first().cloned().unwrap_or(Value::Null)),
+        _ => Err(error_sequence("first", input)),
+    }
+}
+
+/// Return the last character of a string, or last item of an array.
+///
+/// Returns null when the input is empty.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a string or array.
+pub fn last(input: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+    match input {
+        Value::String(string) => Ok(string.chars().last().map_or(Value::Null, |c| json!(c))),
+        Value::Array(array) => Ok(array.last().cloned().unwrap_or(Value::Null)),
+        _ => Err(error_sequence("last", input)),
+    }
+}
+
+/// Return the characters of a string, or items of an array, in reverse order.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a string or array.
+pub fn reverse(input: &Value, _: &HashMap<String, Value>) -> Result<Value, Error> {
+    match input {
+        Value::String(string) => Ok(json!(string.chars().rev().collect::<String>())),
+        Value::Array(array) => Ok(json!(array.iter().rev().collect::<Vec<_>>())),
+        _ => Err(error_sequence("reverse", input)),
+    }
+}
+
+/// Return the items of an array rendered as strings, and joined by the
+/// `separator` argument.
+///
+/// The `separator` argument defaults to an empty string.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not an array, or the `separator`
+/// argument is not a string.
+pub fn join(input: &Value, args: &HashMap<String, Value>) -> Result<Value, Error> {
+    let array = input.as_array().ok_or_else(|| {
+        Error::build("filter `join` requires array input").with_help(format!(
+            "unable to join `{input}`, only arrays can be joined"
+        ))
+    })?;
+    let separator = match get_argument(args, "separator", 1) {
+        Some(_) => expect_string_argument("join", args, "separator", 1)?,
+        None => "",
+    };
+
+    let mut buffer = String::new();
+    let mut pipe = Pipe::new(&mut buffer);
+    let mut iterator = array.iter().peekable();
+    while let Some(item) = iterator.next() {
+        pipe.write_value(item).map_err(|_| error_write("join"))?;
+        if iterator.peek().is_some() {
+            pipe.write_str(separator).map_err(|_| error_write("join"))?;
+        }
+    }
+
+    Ok(json!(buffer))
+}
+
+/// Return the `value` argument when the input is null, otherwise return
+/// the input.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the `value` argument is missing.
+pub fn default(input: &Value, args: &HashMap<String, Value>) -> Result<Value, Error> {
+    let value = get_argument(args, "value", 1).ok_or_else(|| {
+        Error::build("filter `default` requires argument `value`")
+            .with_help("pass the value to use when the input is null, like `default \"none\"`")
+    })?;
+
+    match input {
+        Value::Null => Ok(value.clone()),
+        _ => Ok(input.clone()),
+    }
+}
+
+/// Return the sum of the input and the `value` argument.
+///
+/// When both numbers are integers the result is an integer, otherwise it is
+/// a float.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input or `value` argument is not a number, or
+/// the result overflows.
+pub fn add(input: &Value, args: &HashMap<String, Value>) -> Result<Value, Error> {
+    let left = input.as_number().ok_or_else(|| {
+        Error::build("filter `add` requires number input").with_help(format!(
+            "unable to add to `{input}`, only numbers can be added"
+        ))
+    })?;
+    let right = get_argument(args, "value", 1)
+        .and_then(|value| value.as_number())
+        .ok_or_else(|| {
+            Error::build("filter `add` requires argument `value`")
+                .with_help("pass the number to add as the first argument, like `add 1`")
+        })?;
+
+    if let (Some(left), Some(right)) = (left.as_i64(), right.as_i64()) {
+        return left
+            .checked_add(right)
+            .map(|sum| json!(sum))
+            .ok_or_else(|| {
+                Error::build("filter `add` overflowed")
+                    .with_help(format!("the sum of `{left}` and `{right}` is too large"))
+            });
+    }
+
+    match (left.as_f64(), right.as_f64()) {
+        (Some(left), Some(right)) => Ok(json!(left + right)),
+        _ => unreachable!("serde_json number must be representable as f64"),
+    }
+}
+
+/// Return the argument with the given name, or the anonymous argument at the
+/// given position if no argument has the name.
+fn get_argument<'args>(
+    args: &'args HashMap<String, Value>,
+    name: &str,
+    position: usize,
+) -> Option<&'args Value> {
+    args.get(name).or_else(|| args.get(&position.to_string()))
+}
+
+/// Return the input as a `&str`.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a string.
+fn expect_string<'input>(filter: &str, input: &'input Value) -> Result<&'input str, Error> {
+    input.as_str().ok_or_else(|| {
+        Error::build(format!("filter `{filter}` requires string input"))
+            .with_help("use quotes to coerce data to string")
+    })
+}
+
+/// Return the argument with the given name or position as a `&str`.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the argument is missing or not a string.
+fn expect_string_argument<'args>(
+    filter: &str,
+    args: &'args HashMap<String, Value>,
+    name: &str,
+    position: usize,
+) -> Result<&'args str, Error> {
+    get_argument(args, name, position)
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| {
+            Error::build(format!("filter `{filter}` requires argument `{name}`")).with_help(
+                format!("pass `{name}` as a string, either by name or as argument {position}"),
+            )
+        })
+}
+
+/// Return an [`Error`] explaining that the filter requires a string or array.
+fn error_sequence(filter: &str, input: &Value) -> Error {
+    Error::build(format!("filter `{filter}` requires string or array input"))
+        .with_help(format!("unable to use `{input}` with `{filter}`"))
+}
+
+/// Return an [`Error`] explaining that the filter failed to build its output.
+fn error_write(filter: &str) -> Error {
+    Error::build("write failure").with_help(format!("filter `{filter}` failed to write its output"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{filter::Error, Engine, Store};
+
+    use serde_json::json;
+
+    #[test]
+    fn test_string() {
+        let store = Store::new().with_must("name", "  taylor Swift ");
+
+        assert_eq!(
+            render(
+                "(( name | trim | upper ))|(( name | trim | lower ))|(( name | trim | capitalize ))",
+                &store
+            ),
+            Ok("TAYLOR SWIFT|taylor swift|Taylor Swift".to_string())
+        );
+        assert_eq!(
+            render("(( name | replace from: \"Swift\", to: \"S.\" ))", &store),
+            Ok("  taylor S. ".to_string())
+        );
+    }
+
+    #[test]
+    fn test_string_error() {
+        let store = Store::new().with_must("number", 1);
+
+        assert_eq!(
+            render("(( number | upper ))", &store),
+            Err(Error::build("filter `upper` requires string input")
+                .with_help("use quotes to coerce data to string"))
+        );
+        assert_eq!(
+            render("(( \"text\" | replace \"t\" ))", &store),
+            Err(Error::build("filter `replace` requires argument `to`")
+                .with_help("pass `to` as a string, either by name or as argument 2"))
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        let store = Store::new().with_must("text", "hello, world");
+
+        assert_eq!(
+            render(
+                "(( text | truncate 5 ))|(( text | truncate 5, \"!\" ))",
+                &store
+            ),
+            Ok("hello...|hello!".to_string())
+        );
+        assert_eq!(
+            render("(( text | truncate 50 ))", &store),
+            Ok("hello, world".to_string())
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        let store = Store::new().with_must("markup", "<b>\"a\" & 'b'</b>");
+
+        assert_eq!(
+            render("(( markup | escape ))", &store),
+            Ok("&lt;b&gt;&quot;a&quot; &amp; &#x27;b&#x27;&lt;/b&gt;".to_string())
+        );
+
+        let mut engine = Engine::default().with_std_filters();
+        engine
+            .insert_template_must("page.html", "(( markup | escape ))")
+            .unwrap();
+        let template = engine.get_template("page.html").unwrap();
+
+        assert_eq!(
+            engine.render(template, &store).unwrap(),
+            "&lt;b&gt;&quot;a&quot; &amp; &#x27;b&#x27;&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn test_sequence() {
+        let store = Store::new()
+            .with_must("text", "abc")
+            .with_must("items", json!([1, 2, 3]))
+            .with_must("empty", json!([]))
+            .with_must("object", json!({"a": 1, "b": 2}));
+
+        assert_eq!(
+            render(
+                "(( text | length ))(( items | length ))(( object | length ))|\
+                (( text | first ))(( text | last ))(( text | reverse ))|\
+                (( items | first ))(( items | last ))(( items | reverse ))|\
+                (( empty | first ))",
+                &store
+            ),
+            Ok("332|accba|13[3, 2, 1]|".to_string())
+        );
+        assert_eq!(
+            render("(( object | first ))", &store),
+            Err(
+                Error::build("filter `first` requires string or array input")
+                    .with_help("unable to use `{\"a\":1,\"b\":2}` with `first`")
+            )
+        );
+    }
+
+    #[test]
+    fn test_join() {
+        let store = Store::new().with_must("items", json!(["a", 1, true, [2, 3]]));
+
+        assert_eq!(
+            render("(( items | join \", \" ))|(( items | join ))", &store),
+            Ok("a, 1, true, [2, 3]|a1true[2, 3]".to_string())
+        );
+    }
+
+    #[test]
+    fn test_default() {
+        let store = Store::new()
+            .with_must("missing", json!(null))
+            .with_must("name", "taylor");
+
+        assert_eq!(
+            render(
+                "(( missing | default \"none\" ))|(( name | default value: \"none\" ))",
+                &store
+            ),
+            Ok("none|taylor".to_string())
+        );
+    }
+
+    #[test]
+    fn test_add() {
+        let store = Store::new()
+            .with_must("integer", 2)
+            .with_must("float", 0.5)
+            .with_must("large", i64::MAX);
+
+        assert_eq!(
+            render("(( integer | add 3 ))|(( float | add 1 ))", &store),
+            Ok("5|1.5".to_string())
+        );
+        assert_eq!(
+            render("(( large | add 1 ))", &store),
+            Err(Error::build("filter `add` overflowed")
+                .with_help(format!("the sum of `{}` and `1` is too large", i64::MAX)))
+        );
+    }
+
+    /// Render the given text with an [`Engine`] that has the standard filters.
+    fn render(text: &str, store: &Store) -> Result<String, Error> {
+        let engine = Engine::default().with_std_filters();
+        let template = engine.compile(text).unwrap();
+
+        engine.render(&template, store)
+    }
+}
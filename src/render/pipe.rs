@@ -1,4 +1,7 @@
-use std::fmt::{Arguments, Display, Result, Write};
+use std::{
+    fmt::{self, Arguments, Display, Result, Write},
+    io,
+};
 
 use serde_json::{Map, Value};
 
@@ -10,7 +13,7 @@ pub struct Pipe<'buffer> {
 
 impl<'buffer> Pipe<'buffer> {
     /// Create a new `Pipe` that writes to the given buffer.
-    pub fn new(buffer: &'buffer mut String) -> Self {
+    pub fn new(buffer: &'buffer mut (dyn Write + 'buffer)) -> Self {
         Self { buffer }
     }
 
@@ -84,6 +87,59 @@ impl Write for Pipe<'_> {
     }
 }
 
+/// Adapts an [`io::Write`] to [`fmt::Write`], so a [`Pipe`] can write to it.
+///
+/// [`fmt::Error`] carries no information, so the underlying [`io::Error`] is
+/// kept and may be retrieved with `take_error` after a write fails.
+pub struct IoWriter<'writer, W>
+where
+    W: io::Write,
+{
+    /// The underlying writer.
+    writer: &'writer mut W,
+    /// The last error returned by the underlying writer.
+    error: Option<io::Error>,
+}
+
+impl<'writer, W> IoWriter<'writer, W>
+where
+    W: io::Write,
+{
+    /// Create a new [`IoWriter`] over the given writer.
+    pub fn new(writer: &'writer mut W) -> Self {
+        Self {
+            writer,
+            error: None,
+        }
+    }
+
+    /// Flush the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] if the underlying writer fails to flush.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Take the last [`io::Error`] returned by the underlying writer, if any.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+}
+
+impl<W> Write for IoWriter<'_, W>
+where
+    W: io::Write,
+{
+    fn write_str(&mut self, s: &str) -> Result {
+        self.writer.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
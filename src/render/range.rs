@@ -0,0 +1,232 @@
+use super::filter::Error;
+
+use serde_json::Value;
+
+const INVALID_RANGE: &str = "invalid range";
+
+/// The largest number of integers a [`Sequence`] may hold when it is collected
+/// into an array.
+pub const MAX_LENGTH: usize = 100_000;
+
+/// A sequence of integers produced by a [`Range`][`crate::compile::tree::Range`].
+///
+/// Each integer is calculated as it is needed, so iterating on a large
+/// `Sequence` does not allocate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sequence {
+    /// The first integer in the [`Sequence`].
+    start: i128,
+    /// The difference between each integer in the [`Sequence`].
+    step: i128,
+    /// The position of the next integer in the [`Sequence`].
+    index: usize,
+    /// The number of integers in the [`Sequence`].
+    length: usize,
+}
+
+impl Sequence {
+    /// Create a new [`Sequence`] from `start` to `end`, counting by `step`.
+    ///
+    /// The `end` is only included when `inclusive` is true, and is reached
+    /// exactly by the `step`. When `step` is positive and `end` is less than
+    /// `start`, or the opposite, the `Sequence` is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if `step` is zero, or the `Sequence` contains more
+    /// integers than can be counted.
+    pub fn new(start: i64, end: i64, step: i64, inclusive: bool) -> Result<Self, Error> {
+        if step == 0 {
+            return Err(
+                Error::build(INVALID_RANGE).with_help("the `step` of a range must not be zero")
+            );
+        }
+
+        let (start, end, step) = (start as i128, end as i128, step as i128);
+        let distance = if step > 0 { end - start } else { start - end };
+        let distance = if inclusive { distance } else { distance - 1 };
+        let length = match distance {
+            distance if distance < 0 => 0,
+            distance => usize::try_from(distance / step.abs() + 1).map_err(|_| {
+                Error::build(INVALID_RANGE)
+                    .with_help("the range contains too many integers to iterate on")
+            })?,
+        };
+
+        Ok(Self {
+            start,
+            step,
+            index: 0,
+            length,
+        })
+    }
+
+    /// Return the [`Sequence`] as a [`Value::Array`] of every integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the `Sequence` contains more than [`MAX_LENGTH`]
+    /// integers.
+    pub fn into_value(self) -> Result<Value, Error> {
+        if self.length > MAX_LENGTH {
+            return Err(Error::build(INVALID_RANGE).with_help(format!(
+                "the range contains {} integers, but only {MAX_LENGTH} can be used as \
+                an array, iterate on it with a `for` block instead",
+                self.length
+            )));
+        }
+
+        Ok(Value::Array(self.map(Value::from).collect()))
+    }
+}
+
+impl Iterator for Sequence {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.length {
+            return None;
+        }
+        let next = self.start + self.index as i128 * self.step;
+        self.index += 1;
+
+        Some(next as i64)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.length - self.index;
+
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Sequence {}
+
+/// Return the integer within the [`Value`], which is used as the named part
+/// of a [`Sequence`].
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the `Value` is not an integer.
+pub fn expect_integer(value: &Value, name: &str) -> Result<i64, Error> {
+    value.as_i64().ok_or_else(|| {
+        Error::build(INVALID_RANGE).with_help(format!(
+            "the {name} of a range must be an integer, found `{value}`"
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Engine, Store};
+
+    use super::{Sequence, MAX_LENGTH};
+
+    use serde_json::json;
+
+    #[test]
+    fn test_sequence() {
+        let collect = |start, end, step, inclusive| {
+            Sequence::new(start, end, step, inclusive)
+                .unwrap()
+                .collect::<Vec<i64>>()
+        };
+
+        assert_eq!(collect(0, 4, 1, false), vec![0, 1, 2, 3]);
+        assert_eq!(collect(0, 4, 1, true), vec![0, 1, 2, 3, 4]);
+        assert_eq!(collect(0, 5, 2, false), vec![0, 2, 4]);
+        assert_eq!(collect(0, 4, 2, false), vec![0, 2]);
+        assert_eq!(collect(3, 0, -1, false), vec![3, 2, 1]);
+        assert_eq!(collect(3, 0, -2, true), vec![3, 1]);
+        assert_eq!(collect(3, 0, 1, false), Vec::<i64>::new());
+        assert_eq!(collect(0, 0, 1, false), Vec::<i64>::new());
+        assert_eq!(collect(0, 0, 1, true), vec![0]);
+        assert_eq!(
+            collect(i64::MAX - 1, i64::MAX, 1, true),
+            vec![i64::MAX - 1, i64::MAX]
+        );
+        assert!(Sequence::new(0, 1, 0, false).is_err());
+    }
+
+    #[test]
+    fn test_sequence_large() {
+        let sequence = Sequence::new(0, i64::MAX, 1, false).unwrap();
+
+        assert_eq!(sequence.len(), i64::MAX as usize);
+        assert!(Sequence::new(i64::MIN, i64::MAX, 1, true).is_err());
+    }
+
+    #[test]
+    fn test_render_range() {
+        let engine = Engine::default();
+        let template = engine
+            .compile(
+                "(* for i in 0..count *)(( i ))(* end *)|\
+                (* for i in 1..=count step 2 *)(( i ))(* end *)|\
+                (* for i in count..0 step -1 *)(( i ))(* end *)|\
+                (* for i in 0..0 *)(( i ))(* else *)empty(* end *)|\
+                (( 1..4 ))",
+            )
+            .unwrap();
+        let store = Store::new().with_must("count", 5);
+
+        assert_eq!(
+            engine.render(&template, &store).unwrap(),
+            "01234|135|54321|empty|[1, 2, 3]"
+        );
+    }
+
+    #[test]
+    fn test_sequence_into_value() {
+        assert_eq!(
+            Sequence::new(0, 3, 1, false).unwrap().into_value(),
+            Ok(json!([0, 1, 2]))
+        );
+        assert!(Sequence::new(0, MAX_LENGTH as i64, 1, false)
+            .unwrap()
+            .into_value()
+            .is_ok());
+        assert!(Sequence::new(0, MAX_LENGTH as i64, 1, true)
+            .unwrap()
+            .into_value()
+            .is_err());
+    }
+
+    #[test]
+    fn test_render_range_too_long() {
+        let engine = Engine::default();
+        let store = Store::new().with_must("count", i64::MAX);
+
+        for source in [
+            "(( 0..count ))",
+            "(* let r = 0..count *)",
+            "(* if 0..count *)(* end *)",
+        ] {
+            let template = engine.compile(source).unwrap();
+            let error = engine.render(&template, &store).unwrap_err();
+            assert!(format!("{error:?}").contains("iterate on it with a `for` block"));
+        }
+
+        let template = engine
+            .compile("(* for i in 0..count *)(( i ))(* break *)(* end *)")
+            .unwrap();
+        assert_eq!(engine.render(&template, &store).unwrap(), "0");
+    }
+
+    #[test]
+    fn test_render_range_invalid() {
+        let engine = Engine::default();
+        let store = Store::new()
+            .with_must("count", 1.5)
+            .with_must("name", json!("taylor"));
+
+        for source in [
+            "(* for i in 0..count *)(* end *)",
+            "(* for i in name..3 *)(* end *)",
+            "(* for i in 0..3 step 0 *)(* end *)",
+        ] {
+            let template = engine.compile(source).unwrap();
+            assert!(engine.render(&template, &store).is_err());
+        }
+    }
+}
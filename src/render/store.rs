@@ -1,4 +1,7 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
 
 use crate::log::Error;
 
@@ -10,6 +13,8 @@ use serde_json::{to_value, Value};
 #[derive(Debug)]
 pub struct Store {
     data: HashMap<String, Value>,
+    /// Keys whose values are safe to render without escaping.
+    safe: HashSet<String>,
 }
 
 impl Store {
@@ -26,6 +31,7 @@ impl Store {
     pub fn new() -> Self {
         Self {
             data: HashMap::new(),
+            safe: HashSet::new(),
         }
     }
 
@@ -139,6 +145,62 @@ impl Store {
         self
     }
 
+    /// Mark the value of the key as safe to render without escaping, such as
+    /// HTML that was already escaped or sanitized.
+    ///
+    /// The mark applies to the value and everything within it, even if the key
+    /// is inserted again, but not to a value with the same name that is assigned
+    /// within the [`Template`][`crate::Template`], like a loop variable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ban::Store;
+    ///
+    /// let mut store = Store::new().with_must("icon", "<svg></svg>");
+    /// store.set_safe("icon");
+    ///
+    /// assert!(store.is_safe("icon"));
+    /// ```
+    #[inline]
+    pub fn set_safe<S>(&mut self, key: S)
+    where
+        S: Into<String>,
+    {
+        self.safe.insert(key.into());
+    }
+
+    /// Mark the value of the key as safe to render without escaping, such as
+    /// HTML that was already escaped or sanitized.
+    ///
+    /// Returns the `Store`, so additional methods may be chained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ban::Store;
+    ///
+    /// let store = Store::new()
+    ///     .with_must("icon", "<svg></svg>")
+    ///     .with_safe("icon");
+    /// ```
+    #[inline]
+    pub fn with_safe<S>(mut self, key: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.set_safe(key);
+
+        self
+    }
+
+    /// Return true if the value of the key is marked as safe to render without
+    /// escaping.
+    #[inline]
+    pub fn is_safe(&self, key: &str) -> bool {
+        self.safe.contains(key)
+    }
+
     /// Returns a reference to the [`Value`] corresponding to the key.
     ///
     /// # Examples
@@ -179,6 +241,8 @@ impl Store {
 pub struct Shadow<'store> {
     pub store: &'store Store,
     data: Vec<HashMap<String, Value>>,
+    /// Keys in each frame whose values are safe to render without escaping.
+    safe: Vec<HashSet<String>>,
 }
 
 impl<'store> Shadow<'store> {
@@ -188,6 +252,7 @@ impl<'store> Shadow<'store> {
         Self {
             store,
             data: vec![HashMap::new()],
+            safe: vec![HashSet::new()],
         }
     }
 
@@ -195,6 +260,7 @@ impl<'store> Shadow<'store> {
     #[inline]
     pub fn push(&mut self) {
         self.data.push(HashMap::new());
+        self.safe.push(HashSet::new());
     }
 
     /// Remove the top frame from the [`Shadow`].
@@ -205,6 +271,7 @@ impl<'store> Shadow<'store> {
         }
 
         self.data.pop();
+        self.safe.pop();
     }
 
     /// Insert the value into the top level stack of the [`Shadow`].
@@ -218,10 +285,49 @@ impl<'store> Shadow<'store> {
         S: Into<String>,
         T: Serialize + Display,
     {
+        self.insert_safe_must(key, value, false);
+    }
+
+    /// Insert the value into the top level stack of the [`Shadow`], marking
+    /// it as safe to render without escaping when `safe` is true.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no frames exist within the [`Shadow`].
+    pub(crate) fn insert_safe_must<S, T>(&mut self, key: S, value: T, safe: bool)
+    where
+        S: Into<String>,
+        T: Serialize + Display,
+    {
+        let key = key.into();
+        let frame = self
+            .safe
+            .last_mut()
+            .expect("stack must not be empty when shadowing value");
+        if safe {
+            frame.insert(key.clone());
+        } else {
+            frame.remove(&key);
+        }
         self.data
             .last_mut()
             .expect("stack must not be empty when shadowing value")
-            .insert(key.into(), to_value(value).unwrap());
+            .insert(key, to_value(value).unwrap());
+    }
+
+    /// Return true if the value of the given key is safe to render without
+    /// escaping.
+    ///
+    /// The nearest frame holding the key decides, and the store is only
+    /// searched when no frame holds it.
+    pub(crate) fn is_safe(&self, index: &str) -> bool {
+        for (stack, safe) in self.data.iter().zip(self.safe.iter()).rev() {
+            if stack.contains_key(index) {
+                return safe.contains(index);
+            }
+        }
+
+        self.store.is_safe(index)
     }
 
     /// Get the [`Value`] of the given key.
@@ -238,6 +344,12 @@ impl<'store> Shadow<'store> {
 
         self.store.get(index)
     }
+
+    /// Get the [`Value`] of the given key, without searching the store.
+    #[inline]
+    pub(crate) fn get_shadowed(&self, index: &str) -> Option<&Value> {
+        self.data.iter().rev().find_map(|stack| stack.get(index))
+    }
 }
 
 #[cfg(test)]
@@ -282,6 +394,29 @@ mod tests {
         assert_eq!(shadow.get("two"), Some(&json!("two")));
     }
 
+    #[test]
+    fn test_shadow_is_safe() {
+        let store = Store::new()
+            .with_must("one", "one")
+            .with_must("two", "two")
+            .with_safe("one");
+        let mut shadow = Shadow::new(&store);
+        shadow.push();
+        shadow.insert_safe_must("two", "shadowed two", true);
+
+        assert!(shadow.is_safe("one"));
+        assert!(shadow.is_safe("two"));
+        shadow.push();
+        shadow.insert_must("one", "shadowed one");
+
+        assert!(!shadow.is_safe("one"));
+        shadow.pop();
+        shadow.pop();
+
+        assert!(shadow.is_safe("one"));
+        assert!(!shadow.is_safe("two"));
+    }
+
     #[test]
     #[should_panic(expected = "last scope must never be removed")]
     fn test_shadow_pop_empty() {
@@ -0,0 +1,82 @@
+//! Contains the [`Test`] trait, and types useful for creating and using tests.
+//!
+//! A `Test` is a user-defined function that checks a [`Value`] within the
+//! condition of an `if` block, or an inline conditional, with the `is` keyword.
+//! Any struct that implements the `Test` trait, or function matching the
+//! [`apply`][`Test::apply`] method, can be registered as a `Test` on an
+//! [`Engine`][`crate::Engine`].
+//!
+//! Every `Engine` has the tests in the [`library`] registered by default.
+//!
+//! ## Examples
+//!
+//! Tests are applied with `is`, and negated with `is not`. Like filters, they
+//! may accept arguments:
+//!
+//! ```html
+//! (* if count is divisible_by 3 && name is not empty *)
+//! ```
+//!
+//! We'll create a test that checks whether a string is uppercase:
+//!
+//! ```
+//! use std::collections::HashMap;
+//!
+//! use ban::{
+//!     filter::serde::Value,
+//!     test::Error,
+//!     Store,
+//! };
+//!
+//! fn shouting(value: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+//!     match value {
+//!         Value::String(string) => Ok(string.to_uppercase() == *string),
+//!         _ => Err(Error::build("test `shouting` requires string input")
+//!                 .with_help("use quotes to coerce data to string")
+//!              ),
+//!     }
+//! }
+//!
+//! let engine = ban::default().with_test_must("shouting", shouting);
+//! let template = engine
+//!     .compile("(* if name is shouting *)calm down(* end *)")
+//!     .unwrap();
+//! let store = Store::new().with_must("name", "TAYLOR");
+//! let result = engine.render(&template, &store).unwrap();
+//!
+//! assert_eq!(result, "calm down");
+//! ```
+//!
+//! The `defined` and `undefined` tests are built into the renderer, because
+//! they check the [`Store`][`crate::Store`] for a variable rather than its value.
+//! Their names are reserved, so adding a `Test` with either name fails, and
+//! [`get_test`][`crate::Engine::get_test`] does not return them.
+
+pub mod library;
+
+use std::collections::HashMap;
+
+pub use crate::log::Error;
+
+use serde_json::Value;
+
+/// Describes a type that can be used to check a [`Value`].
+pub trait Test: Sync + Send {
+    /// Apply the [`Test`] with the given input [`Value`] and arguments, and
+    /// return true if the `Value` passes.
+    ///
+    /// # Errors
+    ///
+    /// May return an [`Error`] to abort template rendering.
+    fn apply(&self, input: &Value, args: &HashMap<String, Value>) -> Result<bool, Error>;
+}
+
+/// Allows any function with a matching signature to be registered as a [`Test`].
+impl<F> Test for F
+where
+    F: Fn(&Value, &HashMap<String, Value>) -> Result<bool, Error> + Sync + Send,
+{
+    fn apply(&self, value: &Value, args: &HashMap<String, Value>) -> Result<bool, Error> {
+        self(value, args)
+    }
+}
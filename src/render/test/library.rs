@@ -0,0 +1,248 @@
+//! Contains the standard library of [`Test`][`super::Test`] functions.
+//!
+//! These tests are registered on every [`Engine`][`crate::Engine`] by default.
+//! A test with the same name can be registered with
+//! [`with_test_must`][`crate::Engine::with_test_must`] to overwrite one.
+//!
+//! | Name           | Input                  | Arguments |
+//! |----------------|------------------------|-----------|
+//! | `even`         | integer                |           |
+//! | `odd`          | integer                |           |
+//! | `divisible_by` | integer                | `divisor` |
+//! | `empty`        | string, array, object  |           |
+//! | `string`       | any                    |           |
+//! | `number`       | any                    |           |
+//! | `integer`      | any                    |           |
+//! | `float`        | any                    |           |
+//! | `boolean`      | any                    |           |
+//! | `array`        | any                    |           |
+//! | `object`       | any                    |           |
+//! | `null`         | any                    |           |
+//!
+//! The `defined` and `undefined` tests are built into the renderer, so they are
+//! not part of this module.
+//!
+//! Arguments may be given by name, or anonymously in the order shown.
+//!
+//! ## Examples
+//!
+//! ```
+//! use ban::{filter::serde::json, Engine, Store};
+//!
+//! let engine = Engine::default();
+//! let template = engine
+//!     .compile(
+//!         "(* for n in numbers *)\
+//!             (( n if n is even else \"-\" ))\
+//!         (* end *)\
+//!         (* if user is not defined *)!(* end *)",
+//!     )
+//!     .unwrap();
+//! let store = Store::new().with_must("numbers", json!([1, 2, 3, 4]));
+//!
+//! assert_eq!(engine.render(&template, &store).unwrap(), "-2-4!");
+//! ```
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::log::Error;
+
+/// Return true if the input integer is even.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not an integer.
+pub fn even(input: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    Ok(expect_integer("even", input)? % 2 == 0)
+}
+
+/// Return true if the input integer is odd.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not an integer.
+pub fn odd(input: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    Ok(expect_integer("odd", input)? % 2 != 0)
+}
+
+/// Return true if the input integer is divisible by the `divisor` argument.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not an integer, or the `divisor`
+/// argument is missing, not an integer, or zero.
+pub fn divisible_by(input: &Value, args: &HashMap<String, Value>) -> Result<bool, Error> {
+    let integer = expect_integer("divisible_by", input)?;
+    let divisor = args
+        .get("divisor")
+        .or_else(|| args.get("1"))
+        .and_then(as_integer)
+        .filter(|divisor| *divisor != 0)
+        .ok_or_else(|| {
+            Error::build("test `divisible_by` requires argument `divisor`")
+                .with_help("pass `divisor` as a non-zero integer, either by name or as argument 1")
+        })?;
+
+    Ok(integer % divisor == 0)
+}
+
+/// Return true if the input string, array or object has no contents.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a string, array or object.
+pub fn empty(input: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    match input {
+        Value::String(string) => Ok(string.is_empty()),
+        Value::Array(array) => Ok(array.is_empty()),
+        Value::Object(object) => Ok(object.is_empty()),
+        _ => Err(
+            Error::build("test `empty` requires string, array or object input")
+                .with_help(format!("unable to check if `{input}` is empty")),
+        ),
+    }
+}
+
+/// Return true if the input is a string.
+pub fn string(input: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    Ok(input.is_string())
+}
+
+/// Return true if the input is a number.
+pub fn number(input: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    Ok(input.is_number())
+}
+
+/// Return true if the input is an integer.
+pub fn integer(input: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    Ok(input.is_i64() || input.is_u64())
+}
+
+/// Return true if the input is a float.
+pub fn float(input: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    Ok(input.is_f64())
+}
+
+/// Return true if the input is a boolean.
+pub fn boolean(input: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    Ok(input.is_boolean())
+}
+
+/// Return true if the input is an array.
+pub fn array(input: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    Ok(input.is_array())
+}
+
+/// Return true if the input is an object.
+pub fn object(input: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    Ok(input.is_object())
+}
+
+/// Return true if the input is null.
+pub fn null(input: &Value, _: &HashMap<String, Value>) -> Result<bool, Error> {
+    Ok(input.is_null())
+}
+
+/// Return the integer within the [`Value`], or [`None`] if it is not an integer.
+fn as_integer(value: &Value) -> Option<i128> {
+    value
+        .as_i64()
+        .map(i128::from)
+        .or_else(|| value.as_u64().map(i128::from))
+}
+
+/// Return the input as an integer.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not an integer.
+fn expect_integer(test: &str, input: &Value) -> Result<i128, Error> {
+    as_integer(input).ok_or_else(|| {
+        Error::build(format!("test `{test}` requires integer input"))
+            .with_help(format!("unable to use `{input}` with `{test}`"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{log::Error, Engine, Store};
+
+    use serde_json::json;
+
+    #[test]
+    fn test_number() {
+        let store = Store::new()
+            .with_must("items", json!([1, 2, 3, 4, 5, 6]))
+            .with_must("large", json!(u64::MAX));
+
+        assert_eq!(
+            render(
+                "(* for n in items *)\
+                (( \"e\" if n is even else \"o\" ))(( \"!\" if n is divisible_by 3 ))\
+                (* end *)|(* if large is odd && large is not divisible_by divisor: 2 *)a(* end *)",
+                &store
+            ),
+            Ok("oeo!eoe!|a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_number_error() {
+        let store = Store::new().with_must("name", "taylor");
+
+        assert_eq!(
+            render("(* if name is even *)(* end *)", &store),
+            Err(Error::build("test `even` requires integer input")
+                .with_help("unable to use `\"taylor\"` with `even`"))
+        );
+        assert!(render("(* if 4 is divisible_by 0 *)(* end *)", &store).is_err());
+        assert!(render("(* if 4 is divisible_by *)(* end *)", &store).is_err());
+    }
+
+    #[test]
+    fn test_type() {
+        let store = Store::new()
+            .with_must("name", "taylor")
+            .with_must("items", json!([]))
+            .with_must("nothing", json!(null));
+
+        assert_eq!(
+            render(
+                "(( 1 if name is string else 0 ))(( 1 if 1 is number else 0 ))\
+                (( 1 if 1.5 is integer else 0 ))(( 1 if 1.5 is float else 0 ))\
+                (( 1 if true is boolean else 0 ))(( 1 if items is array else 0 ))\
+                (( 1 if items is object else 0 ))(( 1 if nothing is null else 0 ))\
+                (( 1 if name is not null else 0 ))",
+                &store
+            ),
+            Ok("110111011".to_string())
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        let store = Store::new()
+            .with_must("name", "")
+            .with_must("items", json!([1]))
+            .with_must("object", json!({}));
+
+        assert_eq!(
+            render(
+                "(( 1 if name is empty else 0 ))(( 1 if items is empty else 0 ))\
+                (( 1 if object is empty else 0 ))",
+                &store
+            ),
+            Ok("101".to_string())
+        );
+        assert!(render("(( 1 if 1 is empty else 0 ))", &store).is_err());
+    }
+
+    fn render(text: &str, store: &Store) -> Result<String, Error> {
+        let engine = Engine::default();
+        let template = engine.compile(text).unwrap();
+
+        engine.render(&template, store)
+    }
+}
@@ -0,0 +1,26 @@
+/// Describes what happens when a variable in a [`Template`][`crate::Template`]
+/// refers to a value that does not exist in the [`Store`][`crate::Store`].
+///
+/// This applies to every part of a path, so with `(( user.name ))` both a
+/// missing `user` and a `user` without a `name` are undefined, as is an index
+/// beyond the end of an array.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Undefined {
+    /// Return an [`Error`][`crate::filter::Error`] when the first part of the
+    /// path is missing, like [`Strict`][`Undefined::Strict`], but evaluate a
+    /// missing key or index after it as null.
+    ///
+    /// So `(( user.name ))` fails when there is no `user`, but renders nothing
+    /// when `user` has no `name`.
+    #[default]
+    Shallow,
+    /// Return an [`Error`][`crate::filter::Error`] that points at the part of
+    /// the path which is missing.
+    Strict,
+    /// Evaluate the variable as null, which renders as nothing.
+    Lenient,
+    /// Evaluate the variable as null, but render a placeholder such as
+    /// `[undefined: user.name]` for any output expression that uses it and
+    /// renders null, so `(( user.name ?? "anon" ))` still renders `anon`.
+    Debug,
+}